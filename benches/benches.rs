@@ -1,19 +1,56 @@
+use std::{collections::BTreeMap, fs, path::Path, sync::Arc};
+
+use aoc2018::{metrics::Registry, Context, Fact};
 use criterion::{black_box, criterion_group, criterion_main, Criterion, PlotConfiguration};
 
 fn benchmark(c: &mut Criterion) {
     let mut group = c.benchmark_group("solutions");
     group
         .plot_config(PlotConfiguration::default().summary_scale(criterion::AxisScale::Logarithmic));
+    let mut metrics = BTreeMap::new();
     for (i, solution) in aoc2018::SOLUTIONS.iter().enumerate() {
         let day = i + 1;
-        group.bench_function(format!("Day {day} Part 1"), |b| {
-            b.iter(|| black_box((solution.part1)(black_box(solution.input))));
-        });
-        group.bench_function(format!("Day {day} Part 2"), |b| {
-            b.iter(|| black_box((solution.part2)(black_box(solution.input))));
-        });
+        for (part, variants) in solution.parts().into_iter().enumerate() {
+            let part = part + 1;
+            for variant in variants {
+                let name = if variants.len() == 1 {
+                    format!("Day {day} Part {part}")
+                } else {
+                    format!("Day {day} Part {part} ({})", variant.name)
+                };
+                // Measure the work done once, outside the timed loop.
+                let registry = Arc::new(Registry::default());
+                let ctx = Context::default().with_metrics(registry.clone());
+                let _ = (variant.run)(solution.input, &ctx);
+                let facts = registry.facts();
+                if !facts.is_empty() {
+                    metrics.insert(name.clone(), Fact::table(facts));
+                }
+                group.bench_function(name, |b| {
+                    b.iter(|| {
+                        black_box((variant.run)(
+                            black_box(solution.input),
+                            &Context::default(),
+                        ))
+                    });
+                });
+            }
+        }
     }
     group.finish();
+    write_metrics(&metrics);
+}
+
+/// Saves the work each solution did next to criterion's timings, so that
+/// algorithms can be compared by both.
+fn write_metrics(metrics: &BTreeMap<String, Fact>) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("target/criterion/metrics.json");
+    let json = serde_json::to_string_pretty(metrics).expect("metrics are serializable");
+    if let Err(error) =
+        fs::create_dir_all(path.parent().unwrap()).and_then(|_| fs::write(&path, json))
+    {
+        eprintln!("Could not write {}: {}", path.display(), error);
+    }
 }
 
 criterion_group!(benches, benchmark);
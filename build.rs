@@ -0,0 +1,71 @@
+//! Hashes the sources into `AOC2018_BUILD_ID`, which identifies the build in
+//! the answer cache without reading the executable at run time.
+
+use std::{env, fs, io, path::Path};
+
+fn main() -> io::Result<()> {
+    let mut hasher = Fnv::default();
+    hasher.write(env::var("CARGO_PKG_VERSION").unwrap_or_default().as_bytes());
+    // Days left out of the build fail rather than answer, but hash the
+    // features anyway so that no build ever reuses another's answers.
+    let mut features: Vec<_> = env::vars()
+        .filter(|(name, _)| name.starts_with("CARGO_FEATURE_"))
+        .map(|(name, _)| name)
+        .collect();
+    features.sort();
+    for feature in features {
+        hasher.write(feature.as_bytes());
+    }
+    for file in ["Cargo.toml", "Cargo.lock"] {
+        println!("cargo:rerun-if-changed={}", file);
+        hash_file(&mut hasher, Path::new(file))?;
+    }
+    println!("cargo:rerun-if-changed=src");
+    hash_dir(&mut hasher, Path::new("src"))?;
+    println!("cargo:rustc-env=AOC2018_BUILD_ID={}", hasher.0);
+    Ok(())
+}
+
+/// Hashes every file under `dir` with its path, in a fixed order.
+fn hash_dir(hasher: &mut Fnv, dir: &Path) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            hash_dir(hasher, &path)?;
+        } else {
+            hash_file(hasher, &path)?;
+        }
+    }
+    Ok(())
+}
+
+fn hash_file(hasher: &mut Fnv, path: &Path) -> io::Result<()> {
+    hasher.write(path.to_string_lossy().as_bytes());
+    hasher.write(&[0]);
+    match fs::read(path) {
+        Ok(contents) => hasher.write(&contents),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+        Err(error) => return Err(error),
+    }
+    Ok(())
+}
+
+/// The same FNV-1a hash as `cache::hash`.
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
@@ -0,0 +1,51 @@
+/* Generated by `aoc2018::ffi::header`, do not edit. */
+#ifndef AOC2018_H
+#define AOC2018_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The number of days, some of which may be unsolved. */
+#define AOC2018_DAYS 25
+
+/* Returned by aoc2018_solve when solving fails. */
+#define AOC2018_ERROR -1
+
+/* How many milliseconds aoc2018_solve lets a solution run before giving up. */
+#define AOC2018_DEFAULT_TIMEOUT_MS 60000
+
+/*
+ * Solves `part` (1 or 2) of `day` for the UTF-8 `input` with the default
+ * variant, and copies the answer into `out` as a NUL-terminated string.
+ * Solving gives up after AOC2018_DEFAULT_TIMEOUT_MS.
+ *
+ * Like snprintf, returns the length of the whole answer, which was cut short
+ * if it is `out_len` or more. Returns AOC2018_ERROR if solving failed.
+ */
+int64_t aoc2018_solve(uint32_t day, uint32_t part, const uint8_t *input,
+                      size_t input_len, char *out, size_t out_len);
+
+/*
+ * Like aoc2018_solve, but gives up after `timeout_ms` milliseconds, or never
+ * if it is 0.
+ */
+int64_t aoc2018_solve_with_timeout(uint32_t day, uint32_t part,
+                                   const uint8_t *input, size_t input_len,
+                                   uint64_t timeout_ms, char *out,
+                                   size_t out_len);
+
+/*
+ * Copies the message of the last error on this thread into `out` and returns
+ * its length, which is 0 if the last call succeeded.
+ */
+int64_t aoc2018_last_error(char *out, size_t out_len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,43 @@
+/* Calls the C interface the way the harness does, run by `ffi::tests`. */
+#include <stdio.h>
+#include <string.h>
+
+#include "aoc2018.h"
+
+#define CHECK(condition)                                                 \
+    if (!(condition)) {                                                  \
+        fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, \
+                #condition);                                             \
+        return 1;                                                        \
+    }
+
+int main(void) {
+    const char *input = "+1\n-2\n+3\n+1\n";
+    char out[64];
+
+    CHECK(aoc2018_solve(1, 1, (const uint8_t *)input, strlen(input), out,
+                        sizeof out) == 1);
+    CHECK(strcmp(out, "3") == 0);
+    CHECK(aoc2018_last_error(out, sizeof out) == 0);
+
+    /* Answers that do not fit are cut short, like snprintf. */
+    CHECK(aoc2018_solve(1, 2, (const uint8_t *)"+7\n-7\n", 6, out, 1) == 1);
+    CHECK(out[0] == '\0');
+
+    /* A frequency that never repeats gives up once the timeout passes. */
+    CHECK(aoc2018_solve_with_timeout(1, 2, (const uint8_t *)"+1\n", 3, 50, out,
+                                     sizeof out) == AOC2018_ERROR);
+    aoc2018_last_error(out, sizeof out);
+    CHECK(strcmp(out, "timed out") == 0);
+
+    CHECK(aoc2018_solve(0, 1, (const uint8_t *)input, strlen(input), out,
+                        sizeof out) == AOC2018_ERROR);
+    int64_t length = aoc2018_last_error(out, sizeof out);
+    CHECK(length == (int64_t)strlen(out));
+    CHECK(strcmp(out, "Day 0 is not solved") == 0);
+
+    CHECK(aoc2018_solve(1, 1, (const uint8_t *)"one\n", 4, out, sizeof out) ==
+          AOC2018_ERROR);
+    CHECK(aoc2018_last_error(NULL, 0) > 0);
+    return 0;
+}
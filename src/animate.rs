@@ -0,0 +1,319 @@
+//! Playing back a day's intermediate states in the terminal, for days that
+//! are easier to debug by watching them.
+
+use std::{
+    fs,
+    io::{self, Write},
+    iter::Fuse,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    terminal::{self, ClearType},
+};
+use eyre::Result;
+
+/// The text frames of an animation, produced lazily.
+pub type Frames = Box<dyn Iterator<Item = Result<String>>>;
+
+/// Produces the frames of a day's animation from its input.
+pub type Animate = fn(&str) -> Result<Frames>;
+
+/// The slowest and fastest frame rates playback can be set to.
+const FPS_RANGE: (f64, f64) = (0.5, 120.0);
+
+/// Something the viewer asked the player to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    TogglePause,
+    /// Pauses and shows the next frame.
+    Step,
+    /// Pauses and shows the previous frame.
+    Back,
+    Faster,
+    Slower,
+    /// Rewinds to the first frame.
+    Restart,
+    Quit,
+}
+
+impl Command {
+    fn from_key(key: KeyEvent) -> Option<Self> {
+        if key.kind == KeyEventKind::Release {
+            return None;
+        }
+        Some(match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Command::Quit,
+            KeyCode::Char(' ') | KeyCode::Char('p') => Command::TogglePause,
+            KeyCode::Right | KeyCode::Char('.') | KeyCode::Char('l') => Command::Step,
+            KeyCode::Left | KeyCode::Char(',') | KeyCode::Char('h') => Command::Back,
+            KeyCode::Up | KeyCode::Char('+') | KeyCode::Char('=') => Command::Faster,
+            KeyCode::Down | KeyCode::Char('-') => Command::Slower,
+            KeyCode::Home | KeyCode::Char('r') => Command::Restart,
+            KeyCode::Esc | KeyCode::Char('q') => Command::Quit,
+            _ => return None,
+        })
+    }
+}
+
+/// Playback state, independent of the terminal.
+///
+/// Every frame produced is kept so that the viewer can rewind.
+pub struct Player {
+    source: Fuse<Frames>,
+    frames: Vec<String>,
+    current: usize,
+    paused: bool,
+    fps: f64,
+}
+
+impl Player {
+    pub fn new(frames: Frames, fps: f64) -> Self {
+        Self {
+            source: frames.fuse(),
+            frames: Vec::new(),
+            current: 0,
+            paused: false,
+            fps: fps.clamp(FPS_RANGE.0, FPS_RANGE.1),
+        }
+    }
+
+    /// The frame being shown, or `None` if there are no frames at all.
+    pub fn frame(&self) -> Option<&str> {
+        self.frames.get(self.current).map(String::as_str)
+    }
+
+    /// The index of the frame being shown.
+    pub fn position(&self) -> usize {
+        self.current
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn fps(&self) -> f64 {
+        self.fps
+    }
+
+    /// Whether every frame has been produced, and so the total is known.
+    pub fn is_complete(&self) -> bool {
+        // Fusing the source makes this exact once it has run out.
+        self.source.size_hint().1 == Some(0)
+    }
+
+    /// The number of frames produced so far.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Moves to the next frame, producing it if necessary. Returns whether
+    /// there was a next frame.
+    pub fn forward(&mut self) -> Result<bool> {
+        if self.frames.is_empty() || self.current + 1 == self.frames.len() {
+            match self.source.next() {
+                Some(frame) => self.frames.push(frame?),
+                None => return Ok(false),
+            }
+            if self.frames.len() == 1 {
+                return Ok(true);
+            }
+        }
+        self.current += 1;
+        Ok(true)
+    }
+
+    /// Moves to the previous frame. Returns whether there was one.
+    pub fn back(&mut self) -> bool {
+        match self.current.checked_sub(1) {
+            Some(previous) => {
+                self.current = previous;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Carries out `command`, returning `false` if playback should stop.
+    pub fn apply(&mut self, command: Command) -> Result<bool> {
+        match command {
+            Command::TogglePause => self.paused = !self.paused,
+            Command::Step => {
+                self.paused = true;
+                self.forward()?;
+            }
+            Command::Back => {
+                self.paused = true;
+                self.back();
+            }
+            Command::Faster => self.fps = (self.fps * 2.0).min(FPS_RANGE.1),
+            Command::Slower => self.fps = (self.fps / 2.0).max(FPS_RANGE.0),
+            Command::Restart => self.current = 0,
+            Command::Quit => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Advances one frame if playing, pausing on the last frame.
+    pub fn tick(&mut self) -> Result<()> {
+        if !self.paused && !self.forward()? {
+            self.paused = true;
+        }
+        Ok(())
+    }
+
+    fn status(&self) -> String {
+        let total = if self.is_complete() {
+            self.len().to_string()
+        } else {
+            format!("{}+", self.len())
+        };
+        format!(
+            "frame {}/{} | {} fps | {} | space: pause, \u{2190}/\u{2192}: step, \
+             +/-: speed, r: restart, q: quit",
+            self.current + 1,
+            total,
+            self.fps,
+            if self.paused { "paused" } else { "playing" },
+        )
+    }
+}
+
+/// Plays `frames` on the terminal's alternate screen until the viewer quits.
+pub fn play(frames: Frames, fps: f64) -> Result<()> {
+    let mut player = Player::new(frames, fps);
+    player.forward()?;
+    let mut stdout = io::stdout();
+    let _screen = AlternateScreen::enter(&mut stdout)?;
+    loop {
+        draw(&mut stdout, &player)?;
+        let deadline = Instant::now() + Duration::from_secs_f64(1.0 / player.fps());
+        let mut ticked = false;
+        while !ticked {
+            let timeout = if player.is_paused() {
+                // Nothing changes until a key is pressed.
+                Duration::from_secs(3600)
+            } else {
+                deadline.saturating_duration_since(Instant::now())
+            };
+            if event::poll(timeout)? {
+                if let Event::Key(key) = event::read()? {
+                    if let Some(command) = Command::from_key(key) {
+                        if !player.apply(command)? {
+                            return Ok(());
+                        }
+                        break;
+                    }
+                }
+            } else {
+                player.tick()?;
+                ticked = true;
+            }
+        }
+    }
+}
+
+fn draw(out: &mut impl Write, player: &Player) -> Result<()> {
+    queue!(out, cursor::MoveTo(0, 0), terminal::Clear(ClearType::All))?;
+    // The terminal is in raw mode, so newlines do not return the cursor.
+    for line in player.frame().unwrap_or("(no frames)").lines() {
+        write!(out, "{}\r\n", line)?;
+    }
+    write!(out, "\r\n{}", player.status())?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Switches the terminal into raw mode on the alternate screen, and back
+/// again when dropped, even if playback fails.
+struct AlternateScreen;
+
+impl AlternateScreen {
+    fn enter(out: &mut impl Write) -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for AlternateScreen {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Writes each frame to its own `frame-NNNNN.txt` file in `dir`, creating it
+/// if needed, and returns how many frames there were.
+///
+/// This plays an animation without a terminal, for tests and for diffing.
+pub fn write_frames(frames: Frames, dir: &Path) -> Result<usize> {
+    fs::create_dir_all(dir)?;
+    let mut count = 0;
+    for frame in frames {
+        fs::write(dir.join(format!("frame-{:05}.txt", count)), frame?)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counting(n: usize) -> Frames {
+        Box::new((0..n).map(|i| Ok(i.to_string())))
+    }
+
+    #[test]
+    fn step_and_rewind() {
+        let mut player = Player::new(counting(3), 10.0);
+        assert!(player.forward().unwrap());
+        assert_eq!(Some("0"), player.frame());
+        player.apply(Command::Step).unwrap();
+        assert!(player.is_paused());
+        assert_eq!(Some("1"), player.frame());
+        player.apply(Command::Back).unwrap();
+        player.apply(Command::Back).unwrap();
+        assert_eq!(Some("0"), player.frame());
+        assert!(!player.is_complete());
+        player.apply(Command::TogglePause).unwrap();
+        for _ in 0..5 {
+            player.tick().unwrap();
+        }
+        assert_eq!(Some("2"), player.frame());
+        assert!(player.is_complete());
+        assert!(player.is_paused());
+        player.apply(Command::Restart).unwrap();
+        assert_eq!(0, player.position());
+        assert!(!player.apply(Command::Quit).unwrap());
+    }
+
+    #[test]
+    fn speed_is_clamped() {
+        let mut player = Player::new(counting(0), 100.0);
+        player.apply(Command::Faster).unwrap();
+        assert_eq!(FPS_RANGE.1, player.fps());
+        assert!(!player.forward().unwrap());
+        assert_eq!(None, player.frame());
+    }
+
+    #[test]
+    fn headless() {
+        let dir = std::env::temp_dir().join(format!("aoc2018-frames-{}", std::process::id()));
+        assert_eq!(3, write_frames(counting(3), &dir).unwrap());
+        assert_eq!(
+            "2",
+            fs::read_to_string(dir.join("frame-00002.txt")).unwrap()
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
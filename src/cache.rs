@@ -0,0 +1,201 @@
+//! An on-disk cache of answers, so that slow parts are only solved once per
+//! input and build.
+
+use std::{
+    env, fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::Params;
+
+/// Overrides where the cache is kept.
+pub const DIR_VAR: &str = "AOC2018_CACHE_DIR";
+
+/// Identifies one answer.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Key<'a> {
+    pub day: usize,
+    pub part: usize,
+    pub variant: &'a str,
+    /// The [`hash`] of the puzzle input.
+    pub input: u64,
+    /// The [`hash`] of any parameter overrides, see [`hash_params`].
+    pub params: u64,
+    /// Which build of the solutions produced the answer, see [`build_id`].
+    pub build: u64,
+}
+
+impl Key<'_> {
+    fn file_name(&self) -> String {
+        format!(
+            "day{:02}-part{}-{}-{:016x}-{:016x}-{:016x}",
+            self.day, self.part, self.variant, self.input, self.params, self.build
+        )
+    }
+}
+
+/// A directory of cached answers, one file per [`Key`].
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The cache in `$AOC2018_CACHE_DIR`, or else in `aoc2018` under the
+    /// user's cache directory.
+    pub fn default_location() -> Option<Self> {
+        if let Some(dir) = env::var_os(DIR_VAR) {
+            return Some(Self::new(dir));
+        }
+        let cache_home = env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+            .or_else(|| env::var_os("LOCALAPPDATA").map(PathBuf::from))?;
+        Some(Self::new(cache_home.join("aoc2018")))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The stored answer for `key`, if there is one.
+    pub fn get(&self, key: &Key) -> Option<String> {
+        fs::read_to_string(self.dir.join(key.file_name())).ok()
+    }
+
+    pub fn put(&self, key: &Key, answer: &str) -> io::Result<()> {
+        static WRITES: AtomicU64 = AtomicU64::new(0);
+
+        fs::create_dir_all(&self.dir)?;
+        // Write then rename so that a concurrent run never reads half an
+        // answer. Each write has its own temporary file, so that two
+        // processes storing the same answer cannot rename each other's.
+        let name = key.file_name();
+        let write = WRITES.fetch_add(1, Ordering::Relaxed);
+        let partial = self
+            .dir
+            .join(format!("{}.{}-{}.partial", name, process::id(), write));
+        fs::write(&partial, answer)?;
+        fs::rename(partial, self.dir.join(name))
+    }
+
+    /// Deletes every stored answer, leaving anything else in the directory
+    /// alone since it may have been set to one that is not the cache's own.
+    pub fn clear(&self) -> io::Result<()> {
+        let entries = match fs::read_dir(&self.dir) {
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(()),
+            entries => entries?,
+        };
+        for entry in entries {
+            let entry = entry?;
+            if is_cache_file(&entry.file_name().to_string_lossy()) && entry.file_type()?.is_file() {
+                match fs::remove_file(entry.path()) {
+                    Err(error) if error.kind() == ErrorKind::NotFound => {}
+                    result => result?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether `name` is one [`Cache::put`] writes: `dayNN-partN-...`.
+fn is_cache_file(name: &str) -> bool {
+    let bytes = name.as_bytes();
+    bytes.len() > 10
+        && name.starts_with("day")
+        && bytes[3..5].iter().all(u8::is_ascii_digit)
+        && name[5..].starts_with("-part")
+}
+
+/// A 64-bit FNV-1a hash, which unlike the standard library's hashers is the
+/// same across runs and platforms.
+pub fn hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Hashes parameter overrides, so that answers to the puzzle as set are not
+/// mixed up with answers to the same input with different parameters.
+pub fn hash_params(params: &Params) -> u64 {
+    let mut bytes = Vec::new();
+    for (name, value) in params {
+        for part in [name, value] {
+            bytes.extend_from_slice(part.as_bytes());
+            bytes.push(0);
+        }
+    }
+    hash(&bytes)
+}
+
+/// Identifies the running build by a hash of its sources taken when it was
+/// compiled, so that answers cached by any other build are never used.
+pub fn build_id() -> u64 {
+    env!("AOC2018_BUILD_ID")
+        .parse()
+        .expect("the build script sets a numeric build ID")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let dir = env::temp_dir().join(format!("aoc2018-cache-{}", std::process::id()));
+        let cache = Cache::new(&dir);
+        let key = Key {
+            day: 11,
+            part: 2,
+            variant: "doubling",
+            input: hash(b"18"),
+            params: hash_params(&Params::new()),
+            build: 1,
+        };
+        assert_eq!(None, cache.get(&key));
+        cache.put(&key, "90,269,16").unwrap();
+        assert_eq!(Some("90,269,16".to_string()), cache.get(&key));
+        assert_eq!(
+            None,
+            cache.get(&Key {
+                build: 2,
+                ..key.clone()
+            })
+        );
+        assert_eq!(
+            None,
+            cache.get(&Key {
+                input: hash(b"42"),
+                ..key.clone()
+            })
+        );
+        assert_eq!(
+            None,
+            cache.get(&Key {
+                params: hash_params(&[("size".to_string(), "3".to_string())].into()),
+                ..key.clone()
+            })
+        );
+        let other = dir.join("notes.txt");
+        fs::write(&other, "not an answer").unwrap();
+        cache.clear().unwrap();
+        assert_eq!(None, cache.get(&key));
+        assert!(other.exists(), "clearing deleted a file it did not write");
+        cache.clear().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn fnv() {
+        assert_eq!(0xcbf2_9ce4_8422_2325, hash(b""));
+        assert_eq!(0xaf63_dc4c_8601_ec8c, hash(b"a"));
+    }
+}
@@ -0,0 +1,220 @@
+//! Project settings read from an `aoc2018.toml` file.
+//!
+//! Each setting is taken from the first of these that sets it:
+//!
+//! 1. a command line flag, such as `--format` or an input path,
+//! 2. an environment variable, see [`INPUT_DIR_VAR`], [`FORMAT_VAR`] and
+//!    `RUST_LOG`,
+//! 3. the config file, `$AOC2018_CONFIG` or else `aoc2018.toml` in the
+//!    current directory,
+//! 4. the built-in default.
+//!
+//! Settings are top-level keys, and each `[dayNN]` table overrides the
+//! parameters of one day with strings, integers, floats or booleans.
+//!
+//! ```toml
+//! input_dir = "inputs"   # relative to this file
+//! format = "json"
+//! log_level = "warn"
+//!
+//! [day07]
+//! workers = 2
+//! base_time = 0
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use eyre::{Result, WrapErr};
+use serde::Deserialize;
+
+use crate::Params;
+
+/// The name of the config file looked for in the current directory.
+pub const FILE_NAME: &str = "aoc2018.toml";
+
+/// Overrides which config file is read.
+pub const PATH_VAR: &str = "AOC2018_CONFIG";
+
+/// Overrides `input_dir`.
+pub const INPUT_DIR_VAR: &str = "AOC2018_INPUT_DIR";
+
+/// Overrides `format`.
+pub const FORMAT_VAR: &str = "AOC2018_FORMAT";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "File")]
+pub struct Config {
+    /// Where to read `dayNN.txt` inputs from instead of the bundled ones.
+    pub input_dir: Option<PathBuf>,
+    /// How to print answers, `text` or `json`.
+    pub format: Option<String>,
+    /// The default tracing filter, such as `info` or `aoc2018=debug`.
+    pub log_level: Option<String>,
+    /// Parameter overrides for each day.
+    pub days: BTreeMap<usize, Params>,
+}
+
+impl Config {
+    /// Reads the config file, if there is one, and applies any overrides from
+    /// the environment.
+    pub fn load() -> Result<Self> {
+        let path = match env::var_os(PATH_VAR) {
+            Some(path) => Some(PathBuf::from(path)),
+            None => Some(PathBuf::from(FILE_NAME)).filter(|path| path.is_file()),
+        };
+        let mut config = match path {
+            Some(path) => Self::read(&path)?,
+            None => Self::default(),
+        };
+        if let Some(dir) = env::var_os(INPUT_DIR_VAR) {
+            config.input_dir = Some(dir.into());
+        }
+        if let Ok(format) = env::var(FORMAT_VAR) {
+            config.format = Some(format);
+        }
+        Ok(config)
+    }
+
+    /// Reads the config file at `path`, resolving `input_dir` relative to it.
+    pub fn read(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .wrap_err_with(|| format!("Could not read {}", path.display()))?;
+        let mut config =
+            Self::parse(&text).wrap_err_with(|| format!("Invalid config {}", path.display()))?;
+        if let (Some(dir), Some(base)) = (&mut config.input_dir, path.parent()) {
+            *dir = base.join(&dir);
+        }
+        Ok(config)
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    /// The parameter overrides for `day`.
+    pub fn params(&self, day: usize) -> Params {
+        self.days.get(&day).cloned().unwrap_or_default()
+    }
+
+    /// The input file for `day` in `input_dir`, if one is set.
+    pub fn input_path(&self, day: usize) -> Option<PathBuf> {
+        self.input_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("day{:02}.txt", day)))
+    }
+}
+
+/// The config file as written, before its tables are checked.
+#[derive(Deserialize)]
+struct File {
+    input_dir: Option<PathBuf>,
+    format: Option<String>,
+    log_level: Option<String>,
+    /// Every other top-level key, which must be a `[dayNN]` table.
+    #[serde(flatten)]
+    days: BTreeMap<String, BTreeMap<String, Param>>,
+}
+
+impl TryFrom<File> for Config {
+    type Error = String;
+
+    fn try_from(file: File) -> Result<Self, String> {
+        let days = file
+            .days
+            .into_iter()
+            .map(|(name, params)| {
+                let day = name
+                    .strip_prefix("day")
+                    .and_then(|number| number.parse().ok())
+                    .ok_or_else(|| {
+                        format!("unknown setting {:?}, tables are named like [day07]", name)
+                    })?;
+                let params = params.into_iter().map(|(key, Param(value))| (key, value));
+                Ok((day, params.collect()))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self {
+            input_dir: file.input_dir,
+            format: file.format,
+            log_level: file.log_level,
+            days,
+        })
+    }
+}
+
+/// A parameter value as the solution will parse it, whatever its TOML type.
+#[derive(Deserialize)]
+#[serde(try_from = "toml::Value")]
+struct Param(String);
+
+impl TryFrom<toml::Value> for Param {
+    type Error = String;
+
+    fn try_from(value: toml::Value) -> Result<Self, String> {
+        match value {
+            toml::Value::String(string) => Ok(Self(string)),
+            toml::Value::Integer(integer) => Ok(Self(integer.to_string())),
+            toml::Value::Float(float) => Ok(Self(float.to_string())),
+            toml::Value::Boolean(boolean) => Ok(Self(boolean.to_string())),
+            other => Err(format!(
+                "expected a string, number or boolean, found {}",
+                other.type_str()
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let config = Config::parse(
+            r#"
+            # Settings for the whole project.
+            input_dir = "my # inputs"
+            log_level = "debug" # trailing comment
+
+            [day07]
+            workers = 2
+            base_time = 0
+            [day12]
+            generations = 5_000
+            "#,
+        )
+        .unwrap();
+        assert_eq!(Some(PathBuf::from("my # inputs")), config.input_dir);
+        assert_eq!(None, config.format);
+        assert_eq!(Some("debug".to_string()), config.log_level);
+        assert_eq!(
+            Some("2"),
+            config.params(7).get("workers").map(String::as_str)
+        );
+        assert_eq!(
+            Some("5000"),
+            config.params(12).get("generations").map(String::as_str)
+        );
+        assert!(config.params(6).is_empty());
+        assert_eq!(
+            Some(PathBuf::from("my # inputs/day07.txt")),
+            config.input_path(7)
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error = Config::parse("format = \"json\"\nlog_level = debug").unwrap_err();
+        assert!(error.to_string().contains("line 2, column 13"), "{}", error);
+        let error = Config::parse("[days]\nworkers = 2").unwrap_err();
+        assert!(error.to_string().contains("\"days\""), "{}", error);
+        assert!(Config::parse("formats = \"text\"").is_err());
+        assert!(Config::parse("format = 3").is_err());
+        assert!(Config::parse("[day07]\nworkers = [2]").is_err());
+        assert!(Config::parse("input_dir = \"unterminated").is_err());
+    }
+}
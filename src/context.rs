@@ -0,0 +1,249 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, AtomicU8, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use eyre::eyre;
+
+use crate::{metrics::Metrics, Explain, Fact, Param, Params};
+
+/// Per-run state handed to every solution.
+#[derive(Clone, Default)]
+pub struct Context {
+    cancel: CancelToken,
+    progress: Option<Arc<dyn Progress>>,
+    explain: Option<Arc<dyn Explain>>,
+    metrics: Option<Arc<dyn Metrics>>,
+    params: Arc<Params>,
+}
+
+impl fmt::Debug for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Context")
+            .field("cancel", &self.cancel)
+            .field("progress", &self.progress.is_some())
+            .field("explain", &self.explain.is_some())
+            .field("metrics", &self.metrics.is_some())
+            .field("params", &self.params)
+            .finish()
+    }
+}
+
+impl Context {
+    pub fn new(cancel: CancelToken) -> Self {
+        Self {
+            cancel,
+            progress: None,
+            explain: None,
+            metrics: None,
+            params: Arc::default(),
+        }
+    }
+
+    /// Sends progress updates from this run to `progress`.
+    pub fn with_progress(mut self, progress: Arc<dyn Progress>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Sends the facts this run reports to `explain`.
+    pub fn with_explain(mut self, explain: Arc<dyn Explain>) -> Self {
+        self.explain = Some(explain);
+        self
+    }
+
+    /// Sends the measurements this run reports to `metrics`.
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Overrides the solution's built-in value of each parameter in `params`.
+    pub fn with_params(mut self, params: Arc<Params>) -> Self {
+        self.params = params;
+        self
+    }
+
+    pub fn cancel_token(&self) -> &CancelToken {
+        &self.cancel
+    }
+
+    /// Fails once the run has been cancelled.
+    ///
+    /// Solutions call this from any loop that is not obviously bounded by the
+    /// size of the input, so that adversarial inputs cannot hang the caller.
+    pub fn check(&self) -> Result<(), Cancelled> {
+        self.cancel.check()
+    }
+
+    /// Like [`Self::check`], but if the run was stopped, wraps the error with
+    /// how far it got, such as `generation 1234`, so that the caller can show
+    /// the partial result.
+    pub fn checkpoint<S>(&self, state: impl FnOnce() -> S) -> eyre::Result<()>
+    where
+        S: fmt::Display + Send + Sync + 'static,
+    {
+        self.check()
+            .map_err(|cancelled| eyre::Report::new(cancelled).wrap_err(state()))
+    }
+
+    /// Reports that `task` has reached `position`, out of `length` if known.
+    ///
+    /// This does nothing unless someone is listening, but callers in hot loops
+    /// should still only report every few thousand iterations.
+    pub fn progress(&self, task: &'static str, position: u64, length: Option<u64>) {
+        if let Some(progress) = &self.progress {
+            progress.update(task, position, length);
+        }
+    }
+
+    /// The value of `param`, which is its default unless it was overridden.
+    pub fn param<T: TryFrom<i64>>(&self, param: &Param) -> eyre::Result<T> {
+        let value = match self.params.get(param.name) {
+            Some(value) => param.parse(value)?,
+            None => param.default,
+        };
+        T::try_from(value).map_err(|_| eyre!("Parameter {} is out of range: {}", param.name, value))
+    }
+
+    /// Whether anyone is listening for facts, so that solutions can skip
+    /// gathering ones that are expensive to build.
+    pub fn explaining(&self) -> bool {
+        self.explain.is_some()
+    }
+
+    /// Reports an intermediate result that helps explain the answer, such as
+    /// which guard was chosen.
+    pub fn explain(&self, name: &'static str, value: impl Into<Fact>) {
+        if let Some(explain) = &self.explain {
+            explain.fact(name, value.into());
+        }
+    }
+
+    /// Adds `amount` to the counter `name`, such as the number of nodes a
+    /// search expanded.
+    pub fn count(&self, name: &'static str, amount: u64) {
+        if let Some(metrics) = &self.metrics {
+            metrics.count(name, amount);
+        }
+    }
+
+    /// Records one sample of the histogram `name`, such as the size of one
+    /// search.
+    pub fn record(&self, name: &'static str, value: u64) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record(name, value);
+        }
+    }
+}
+
+/// Receives progress updates from long-running solutions.
+pub trait Progress: Send + Sync {
+    fn update(&self, task: &'static str, position: u64, length: Option<u64>);
+}
+
+/// Why a run was stopped early.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum CancelReason {
+    Cancelled = 1,
+    TimedOut = 2,
+    /// The user pressed Ctrl-C.
+    Interrupted = 3,
+}
+
+/// A cheaply clonable flag shared between a solution and whoever may stop it.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: AtomicU8,
+    created: Instant,
+    /// Nanoseconds after `created` at which the run times out, or `u64::MAX`
+    /// if it never does.
+    deadline: AtomicU64,
+}
+
+impl Default for Inner {
+    fn default() -> Self {
+        Self {
+            state: AtomicU8::new(0),
+            created: Instant::now(),
+            deadline: AtomicU64::new(u64::MAX),
+        }
+    }
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stops the run; the first reason given wins.
+    pub fn cancel(&self, reason: CancelReason) {
+        let _ = self.inner.state.compare_exchange(
+            0,
+            reason as u8,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Cancels the run with [`CancelReason::TimedOut`] once `timeout` has passed.
+    ///
+    /// The deadline is only compared with the clock when the run is checked,
+    /// so no thread waits for it. The earliest of several deadlines wins.
+    pub fn cancel_after(&self, timeout: Duration) {
+        let deadline = self.inner.created.elapsed().saturating_add(timeout);
+        let nanos = u64::try_from(deadline.as_nanos()).unwrap_or(u64::MAX - 1);
+        self.inner.deadline.fetch_min(nanos, Ordering::Relaxed);
+    }
+
+    pub fn reason(&self) -> Option<CancelReason> {
+        let deadline = self.inner.deadline.load(Ordering::Relaxed);
+        if deadline != u64::MAX
+            && self.inner.state.load(Ordering::Relaxed) == 0
+            && self.inner.created.elapsed().as_nanos() >= u128::from(deadline)
+        {
+            self.cancel(CancelReason::TimedOut);
+        }
+        match self.inner.state.load(Ordering::Relaxed) {
+            0 => None,
+            1 => Some(CancelReason::Cancelled),
+            2 => Some(CancelReason::TimedOut),
+            _ => Some(CancelReason::Interrupted),
+        }
+    }
+
+    pub fn check(&self) -> Result<(), Cancelled> {
+        match self.reason() {
+            None => Ok(()),
+            Some(reason) => Err(Cancelled { reason }),
+        }
+    }
+}
+
+/// The error returned by a solution that was stopped early.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled {
+    pub reason: CancelReason,
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self.reason {
+            CancelReason::Cancelled => "cancelled",
+            CancelReason::TimedOut => "timed out",
+            CancelReason::Interrupted => "interrupted",
+        })
+    }
+}
+
+impl std::error::Error for Cancelled {}
@@ -0,0 +1,165 @@
+//! Finding where a sequence of states starts repeating, so that simulations
+//! too long to run can be skipped ahead.
+//!
+//! [`find`] remembers every state it is given, which suits short sequences
+//! and states that are cheap to hash. [`brent`] only keeps two states, which
+//! suits large states, but needs the whole sequence to be determined by a
+//! step function.
+
+use std::hash::Hash;
+
+use crate::hash::HashMap;
+
+/// A sequence whose states from index `start` on repeat every `period` steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// The index of the first state equal to the state at index `n`.
+    ///
+    /// ```
+    /// use aoc2018::cycle::Cycle;
+    ///
+    /// let cycle = Cycle { start: 2, period: 3 };
+    /// assert_eq!(vec![0, 1, 2, 3, 4, 2, 3, 4, 2], (0..9).map(|n| cycle.index(n)).collect::<Vec<_>>());
+    /// ```
+    pub fn index(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.period
+        }
+    }
+
+    /// The value at index `n` of something derived from the states, which
+    /// changes by the same amount every period once the states repeat, such
+    /// as the sum of positions in a pattern that moves along without changing.
+    ///
+    /// `value` is only asked for indices up to `start + period`.
+    ///
+    /// ```
+    /// use aoc2018::cycle::Cycle;
+    ///
+    /// let values = [5, 1, 10, 11, 12];
+    /// let cycle = Cycle { start: 2, period: 2 };
+    /// assert_eq!(2010, cycle.extrapolate(2002, |i| values[i]));
+    /// assert_eq!(2009, cycle.extrapolate(2001, |i| values[i]));
+    /// ```
+    pub fn extrapolate(&self, n: u64, mut value: impl FnMut(usize) -> i64) -> i64 {
+        let (start, period) = (self.start as u64, self.period as u64);
+        if n <= start + period {
+            return value(n as usize);
+        }
+        let delta = value(self.start + self.period) - value(self.start);
+        let (periods, offset) = ((n - start) / period, (n - start) % period);
+        value(self.start + offset as usize) + delta * periods as i64
+    }
+}
+
+/// Finds the first state in `states` equal to an earlier one, returning
+/// where the cycle starts and the repeated state.
+///
+/// ```
+/// use aoc2018::cycle::{self, Cycle};
+///
+/// let states = [3, 1, 4, 1, 5];
+/// assert_eq!(Some((Cycle { start: 1, period: 2 }, 1)), cycle::find(states));
+/// assert_eq!(None, cycle::find([1, 2, 3]));
+/// ```
+pub fn find<S: Hash + Eq + Clone>(states: impl IntoIterator<Item = S>) -> Option<(Cycle, S)> {
+    find_by_key(states, S::clone)
+}
+
+/// Like [`find`], but compares states by `key`, such as a pattern without
+/// its position.
+pub fn find_by_key<S, K: Hash + Eq>(
+    states: impl IntoIterator<Item = S>,
+    mut key: impl FnMut(&S) -> K,
+) -> Option<(Cycle, S)> {
+    let mut seen = HashMap::default();
+    for (i, state) in states.into_iter().enumerate() {
+        if let Some(start) = seen.insert(key(&state), i) {
+            let cycle = Cycle {
+                start,
+                period: i - start,
+            };
+            return Some((cycle, state));
+        }
+    }
+    None
+}
+
+/// Finds the cycle in the states reached by applying `step` over and over to
+/// `initial`, with Brent's algorithm.
+///
+/// This never returns if the states never repeat, so `step` should only
+/// reach finitely many.
+///
+/// ```
+/// use aoc2018::cycle::{brent, Cycle};
+///
+/// // 3, 9, 27, 81, 43, 29, 87, 61, 83, 49, 47, 41, 23, 69, 7, 21, 63, 89, 67, 1, 3, ...
+/// let cycle = brent(3u32, |&n| n * 3 % 100);
+/// assert_eq!(Cycle { start: 0, period: 20 }, cycle);
+/// ```
+pub fn brent<S: Clone + PartialEq>(initial: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    // Find the period by moving the hare ahead until it meets the tortoise,
+    // which jumps to the hare at every power of two.
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+
+    // Then find the start with the hare one period ahead of the tortoise.
+    let mut tortoise = initial.clone();
+    let mut hare = initial;
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+    Cycle { start, period }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Rng;
+
+    /// Both algorithms agree on random functional graphs.
+    #[test]
+    fn algorithms_agree() {
+        let mut rng = Rng::new(18);
+        for _ in 0..100 {
+            let size = rng.range(1..50) as usize;
+            let next: Vec<_> = (0..size)
+                .map(|_| rng.range(0..size as u64) as usize)
+                .collect();
+            let initial = rng.range(0..size as u64) as usize;
+            let states = std::iter::successors(Some(initial), |&state| Some(next[state]));
+            let (cycle, state) = find(states).unwrap();
+            assert_eq!(cycle, brent(initial, |&state| next[state]));
+            let mut at_start = initial;
+            for _ in 0..cycle.start {
+                at_start = next[at_start];
+            }
+            assert_eq!(at_start, state);
+        }
+    }
+}
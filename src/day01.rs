@@ -1,39 +1,127 @@
-use ahash::AHashSet;
-use eyre::{bail, Result};
+use std::io::BufRead;
+
+use eyre::{bail, eyre, Result};
 use tracing::instrument;
 
-use crate::Solution;
+use crate::{cycle, input, Context, Solution, Variant};
 
 pub const SOLUTION: Solution = Solution {
     input: include_str!("../input/day01.txt"),
-    part1,
-    part2,
+    part1: &[Variant::new("default", part1).with_stream(part1_stream)],
+    part2: &[Variant::new("default", part2)],
+    #[cfg(feature = "render")]
+    render: None,
+    #[cfg(feature = "animate")]
+    animate: None,
+    inspect: None,
+    params: &[],
+    examples: &[],
 };
 
-fn parse(input: &str) -> Vec<i32> {
-    input.lines().map(|l| l.parse().unwrap()).collect()
+/// Parses one signed frequency change per line.
+///
+/// ```
+/// assert_eq!(vec![1, -2, 3], aoc2018::day01::parse("+1\n-2\n+3").unwrap());
+/// ```
+pub fn parse(input: &str) -> Result<Vec<i32>> {
+    input.lines().map(|line| Ok(line.parse()?)).collect()
 }
 
-#[instrument(skip(input))]
-fn part1(input: &str) -> Result<String> {
-    Ok(parse(input).into_iter().sum::<i32>().to_string())
+#[instrument(skip(input, _ctx))]
+fn part1(input: &str, _ctx: &Context) -> Result<String> {
+    let mut sum = 0;
+    for change in parse(input)? {
+        sum = add(sum, change)?;
+    }
+    Ok(sum.to_string())
 }
 
-#[instrument(skip(input))]
-fn part2(input: &str) -> Result<String> {
-    let changes = parse(input);
+#[instrument(skip(reader, _ctx))]
+fn part1_stream(reader: &mut dyn BufRead, _ctx: &Context) -> Result<String> {
+    let mut sum = 0;
+    input::for_each_line(reader, |line| {
+        sum = add(sum, line.parse()?)?;
+        Ok(())
+    })?;
+    Ok(sum.to_string())
+}
+
+/// Applies one frequency change, failing rather than overflowing on inputs
+/// with billions of lines.
+fn add(sum: i64, change: i32) -> Result<i64> {
+    sum.checked_add(change.into())
+        .ok_or(eyre!("The frequency is too large"))
+}
+
+#[instrument(skip(input, ctx))]
+fn part2(input: &str, ctx: &Context) -> Result<String> {
+    first_repeated_frequency(&parse(input)?, ctx).map(|freq| freq.to_string())
+}
+
+/// Finds the first frequency reached twice while applying `changes` over and
+/// over, starting from zero.
+///
+/// This never finishes for some inputs, so it gives up once `ctx` is cancelled.
+///
+/// ```
+/// use aoc2018::{day01::first_repeated_frequency, Context};
+///
+/// let freq = first_repeated_frequency(&[3, 3, 4, -2, -4], &Context::default()).unwrap();
+/// assert_eq!(10, freq);
+/// ```
+pub fn first_repeated_frequency(changes: &[i32], ctx: &Context) -> Result<i32> {
     if changes.is_empty() {
         bail!("Must have some frequency changes");
     }
-    let mut frequencies = changes.iter().copied().cycle().scan(0, |acc, d| {
-        *acc += d;
-        Some(*acc)
-    });
-    let mut seen = AHashSet::default();
-    loop {
-        let freq = frequencies.next().unwrap();
-        if !seen.insert(freq) {
-            return Ok(freq.to_string());
-        }
+    let frequencies = changes
+        .iter()
+        .copied()
+        .cycle()
+        .scan(0, |acc, d| {
+            *acc += d;
+            Some(*acc)
+        })
+        .take_while(|_| ctx.check().is_ok());
+    match cycle::find(frequencies) {
+        Some((_, freq)) => Ok(freq),
+        // The frequencies only end once cancelled.
+        None => Err(ctx.check().unwrap_err().into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{CancelReason, CancelToken, Cancelled};
+
+    #[test]
+    fn overflow() {
+        assert_eq!(
+            "The frequency is too large",
+            add(i64::MAX, 1).unwrap_err().to_string()
+        );
+        let input = "+2147483647\n".repeat(3);
+        let sum = part1_stream(&mut input.as_bytes(), &Context::default()).unwrap();
+        assert_eq!("6442450941", sum);
+    }
+
+    #[test]
+    fn first_repeat() {
+        assert_eq!("2", part2("+1\n-2\n+3\n+1", &Context::default()).unwrap());
+    }
+
+    #[test]
+    fn never_repeats() {
+        let cancel = CancelToken::new();
+        cancel.cancel_after(Duration::from_millis(10));
+        let error = part2("+1", &Context::new(cancel)).unwrap_err();
+        assert_eq!(
+            Some(&Cancelled {
+                reason: CancelReason::TimedOut
+            }),
+            error.downcast_ref::<Cancelled>()
+        );
     }
 }
@@ -1,21 +1,58 @@
-use ahash::AHashMap;
 use eyre::{bail, Result};
 use tracing::instrument;
 
-use crate::Solution;
+use crate::{hash::HashMap, Context, Example, Solution, Variant};
 
 pub const SOLUTION: Solution = Solution {
     input: include_str!("../input/day02.txt"),
-    part1,
-    part2,
+    part1: &[Variant::new("default", part1)],
+    part2: &[Variant::new("default", part2)],
+    #[cfg(feature = "render")]
+    render: None,
+    #[cfg(feature = "animate")]
+    animate: None,
+    inspect: None,
+    params: &[],
+    examples: &[
+        Example {
+            input: "abcdef\nbababc\nabbcde\nabcccd\naabcdd\nabcdee\nababab\n",
+            params: &[],
+            answers: [Some("12"), None],
+        },
+        Example {
+            input: "abcde\nfghij\nklmno\npqrst\nfguij\naxcye\nwvxyz\n",
+            params: &[],
+            answers: [None, Some("fgij")],
+        },
+    ],
 };
 
-#[instrument(skip(input))]
-fn part1(input: &str) -> Result<String> {
-    let (twos, threes) = input.lines().fold((0, 0), |(twos, threes), id| {
+#[instrument(skip(input, _ctx))]
+fn part1(input: &str, _ctx: &Context) -> Result<String> {
+    Ok(checksum(input.lines()).to_string())
+}
+
+#[instrument(skip(input, _ctx))]
+fn part2(input: &str, _ctx: &Context) -> Result<String> {
+    let ids: Vec<_> = input.lines().collect();
+    match common_letters(&ids) {
+        Some(letters) => Ok(letters),
+        None => bail!("No nearly matching ids found"),
+    }
+}
+
+/// Multiplies the number of ids containing a letter exactly twice by the
+/// number containing a letter exactly three times.
+///
+/// ```
+/// let ids = ["abcdef", "bababc", "abbcde", "abcccd", "aabcdd", "abcdee", "ababab"];
+/// assert_eq!(12, aoc2018::day02::checksum(ids));
+/// ```
+pub fn checksum<'a>(ids: impl IntoIterator<Item = &'a str>) -> u32 {
+    let (twos, threes) = ids.into_iter().fold((0, 0), |(twos, threes), id| {
         let mut two = false;
         let mut three = false;
-        let mut counts: AHashMap<char, u32> = AHashMap::default();
+        let mut counts: HashMap<char, u32> = HashMap::default();
         id.chars().for_each(|c| *counts.entry(c).or_default() += 1);
         for (_, i) in counts {
             match i {
@@ -29,12 +66,17 @@ fn part1(input: &str) -> Result<String> {
             threes + if three { 1 } else { 0 },
         )
     });
-    Ok((twos * threes).to_string())
+    twos * threes
 }
 
-#[instrument(skip(input))]
-fn part2(input: &str) -> Result<String> {
-    let ids: Vec<_> = input.lines().collect();
+/// Finds the first pair of ids that differ in exactly one position and
+/// returns the letters they have in common.
+///
+/// ```
+/// let ids = ["abcde", "fghij", "klmno", "pqrst", "fguij", "axcye", "wvxyz"];
+/// assert_eq!(Some("fgij".to_string()), aoc2018::day02::common_letters(&ids));
+/// ```
+pub fn common_letters(ids: &[&str]) -> Option<String> {
     for (i, &id_a) in ids.iter().enumerate() {
         for &id_b in &ids[i + 1..] {
             let mut differences = Iterator::zip(id_a.chars(), id_b.chars())
@@ -50,8 +92,8 @@ fn part2(input: &str) -> Result<String> {
             }
             let mut ret = id_a.to_string();
             ret.remove(difference);
-            return Ok(ret);
+            return Some(ret);
         }
     }
-    bail!("No nearly matching ids found");
+    None
 }
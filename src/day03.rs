@@ -1,71 +1,160 @@
-use ahash::AHashMap;
+use std::str::FromStr;
+
 use eyre::{bail, Result};
-use recap::Recap;
-use serde::Deserialize;
 use tracing::instrument;
 
-use crate::Solution;
+#[cfg(feature = "render")]
+use crate::render::{palette, Image};
+use crate::{
+    grid::{Grid, Point},
+    parse::{self, literal, uint, PResult},
+    Context, Fact, Solution, Variant,
+};
 
 pub const SOLUTION: Solution = Solution {
     input: include_str!("../input/day03.txt"),
-    part1,
-    part2,
+    part1: &[Variant::new("default", part1)],
+    part2: &[Variant::new("default", part2)],
+    #[cfg(feature = "render")]
+    render: Some(render),
+    #[cfg(feature = "animate")]
+    animate: None,
+    inspect: Some(inspect),
+    params: &[],
+    examples: &[],
 };
 
-#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct Vec2 {
-    x: u32,
-    y: u32,
+/// An elf's claim on a rectangle of fabric, parsed from lines like
+/// `#1 @ 1,3: 4x4`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Claim {
+    pub id: u32,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
 }
 
-#[derive(Debug, Clone, Copy, Deserialize, Recap)]
-#[recap(regex = r#"#(?P<id>\d+) @ (?P<x>\d+),(?P<y>\d+): (?P<width>\d+)x(?P<height>\d+)"#)]
-struct Claim {
-    id: u32,
-    x: u32,
-    y: u32,
-    width: u32,
-    height: u32,
+impl Claim {
+    /// Every square inch covered by this claim.
+    pub fn points(self) -> impl Iterator<Item = Point> {
+        let (x, y) = (self.x as usize, self.y as usize);
+        let (width, height) = (self.width as usize, self.height as usize);
+        (x..x + width).flat_map(move |x| (y..y + height).map(move |y| Point::new(x, y)))
+    }
 }
 
-impl Claim {
-    fn points(self) -> impl Iterator<Item = (u32, u32)> {
-        (self.x..self.x + self.width)
-            .flat_map(move |x| (self.y..self.y + self.height).map(move |y| (x, y)))
+impl FromStr for Claim {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        parse::complete(s, claim)
     }
 }
 
-fn parse(input: &str) -> Result<Vec<Claim>> {
-    input.lines().map(|line| Ok(line.try_into()?)).collect()
+fn claim(input: &str) -> PResult<'_, Claim> {
+    let (input, ()) = literal("#")(input)?;
+    let (input, id) = uint(input)?;
+    let (input, ()) = literal(" @ ")(input)?;
+    let (input, x) = uint(input)?;
+    let (input, ()) = literal(",")(input)?;
+    let (input, y) = uint(input)?;
+    let (input, ()) = literal(": ")(input)?;
+    let (input, width) = uint(input)?;
+    let (input, ()) = literal("x")(input)?;
+    let (input, height) = uint(input)?;
+    let claim = Claim {
+        id,
+        x,
+        y,
+        width,
+        height,
+    };
+    Ok((input, claim))
+}
+
+/// Parses one claim per line.
+///
+/// ```
+/// use aoc2018::day03::{parse, Claim};
+///
+/// let claims = parse("#1 @ 1,3: 4x4\n#3 @ 5,5: 2x2").unwrap();
+/// assert_eq!(
+///     Claim { id: 3, x: 5, y: 5, width: 2, height: 2 },
+///     claims[1],
+/// );
+/// assert_eq!(4, claims[1].points().count());
+/// ```
+pub fn parse(input: &str) -> Result<Vec<Claim>> {
+    parse::lines(input, claim)
 }
 
-#[instrument(skip(input))]
-fn part1(input: &str) -> Result<String> {
+/// Counts the claims and finds the rectangle of fabric they cover.
+fn inspect(input: &str) -> Result<Vec<(&'static str, Fact)>> {
     let claims = parse(input)?;
-    let mut claimed_points = AHashMap::<_, u32>::new();
+    let bounds = |edges: fn(&Claim) -> (u32, u32)| {
+        let min = claims.iter().map(|claim| edges(claim).0).min().unwrap_or(0);
+        let max = claims.iter().map(|claim| edges(claim).1).max().unwrap_or(0);
+        (min, max)
+    };
+    let (left, right) = bounds(|claim| (claim.x, claim.x + claim.width));
+    let (top, bottom) = bounds(|claim| (claim.y, claim.y + claim.height));
+    let area: u32 = claims.iter().map(|claim| claim.width * claim.height).sum();
+    Ok(vec![
+        ("claims", claims.len().into()),
+        (
+            "bounding box",
+            Fact::table([
+                ("left", left),
+                ("top", top),
+                ("right", right),
+                ("bottom", bottom),
+            ]),
+        ),
+        ("total claimed area", area.into()),
+    ])
+}
+
+/// How many claims cover each square inch of the fabric.
+pub fn claim_counts(claims: &[Claim]) -> Grid<u32> {
+    let width = claims.iter().map(|claim| claim.x + claim.width).max();
+    let height = claims.iter().map(|claim| claim.y + claim.height).max();
+    let mut counts = Grid::new(width.unwrap_or(0) as usize, height.unwrap_or(0) as usize, 0);
     for claim in claims {
         for point in claim.points() {
-            *claimed_points.entry(point).or_default() += 1;
+            counts[point] += 1;
         }
     }
-    Ok(claimed_points
-        .values()
-        .filter(|&&v| v >= 2)
+    counts
+}
+
+/// Draws how many claims cover each square inch, hotter where more overlap.
+#[cfg(feature = "render")]
+fn render(input: &str, _ctx: &Context) -> Result<Image> {
+    let counts = claim_counts(&parse(input)?);
+    let max = counts.iter().map(|(_, &count)| count).max().unwrap_or(0);
+    Ok(counts.map(|_, &count| match count {
+        0 => palette::BACKGROUND,
+        count => palette::heat(count as f64 / max as f64),
+    }))
+}
+
+#[instrument(skip(input, _ctx))]
+fn part1(input: &str, _ctx: &Context) -> Result<String> {
+    let counts = claim_counts(&parse(input)?);
+    Ok(counts
+        .iter()
+        .filter(|&(_, &count)| count >= 2)
         .count()
         .to_string())
 }
 
-#[instrument(skip(input))]
-fn part2(input: &str) -> Result<String> {
+#[instrument(skip(input, _ctx))]
+fn part2(input: &str, _ctx: &Context) -> Result<String> {
     let claims = parse(input)?;
-    let mut claimed_points = AHashMap::<_, u32>::new();
-    for claim in &claims {
-        for point in claim.points() {
-            *claimed_points.entry(point).or_default() += 1;
-        }
-    }
+    let counts = claim_counts(&claims);
     for claim in claims {
-        if claim.points().all(|point| claimed_points[&point] == 1) {
+        if claim.points().all(|point| counts[point] == 1) {
             return Ok(claim.id.to_string());
         }
     }
@@ -1,27 +1,62 @@
-use std::{ops::Range, str::FromStr};
+use std::{cmp::Reverse, ops::Range, str::FromStr};
 
-use ahash::AHashMap;
-use eyre::{bail, eyre, Result};
+use eyre::{bail, eyre, Result, WrapErr};
 use time::{macros::format_description, PrimitiveDateTime};
 use tracing::instrument;
 
-use crate::Solution;
+use crate::{
+    hash::{HashMap, HashSet},
+    Context, Example, Fact, Solution, Variant,
+};
 
 pub const SOLUTION: Solution = Solution {
     input: include_str!("../input/day04.txt"),
-    part1,
-    part2,
+    part1: &[Variant::new("default", part1)],
+    part2: &[Variant::new("default", part2)],
+    #[cfg(feature = "render")]
+    render: None,
+    #[cfg(feature = "animate")]
+    animate: None,
+    inspect: Some(inspect),
+    params: &[],
+    examples: &[Example {
+        input: EXAMPLE,
+        params: &[],
+        answers: [Some("240"), Some("4455")],
+    }],
 };
 
-#[derive(Debug, Clone, Copy)]
-enum Event {
+/// The example from the puzzle statement.
+const EXAMPLE: &str = "\
+    [1518-11-01 00:00] Guard #10 begins shift\n\
+    [1518-11-01 00:05] falls asleep\n\
+    [1518-11-01 00:25] wakes up\n\
+    [1518-11-01 00:30] falls asleep\n\
+    [1518-11-01 00:55] wakes up\n\
+    [1518-11-01 23:58] Guard #99 begins shift\n\
+    [1518-11-02 00:40] falls asleep\n\
+    [1518-11-02 00:50] wakes up\n\
+    [1518-11-03 00:05] Guard #10 begins shift\n\
+    [1518-11-03 00:24] falls asleep\n\
+    [1518-11-03 00:29] wakes up\n\
+    [1518-11-04 00:02] Guard #99 begins shift\n\
+    [1518-11-04 00:36] falls asleep\n\
+    [1518-11-04 00:46] wakes up\n\
+    [1518-11-05 00:03] Guard #99 begins shift\n\
+    [1518-11-05 00:45] falls asleep\n\
+    [1518-11-05 00:55] wakes up\n\
+";
+
+/// A line of the guard log, such as `[1518-11-01 00:05] falls asleep`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
     ShiftStart { time: PrimitiveDateTime, guard: u32 },
     FellAsleep { time: PrimitiveDateTime },
     WokeUp { time: PrimitiveDateTime },
 }
 
 impl Event {
-    fn time(self) -> PrimitiveDateTime {
+    pub fn time(self) -> PrimitiveDateTime {
         match self {
             Event::ShiftStart { time, .. }
             | Event::FellAsleep { time }
@@ -34,36 +69,47 @@ impl FromStr for Event {
     type Err = eyre::Report;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (time, action) = s.split_at(18);
+        let (time, action) = s
+            .strip_prefix('[')
+            .and_then(|s| s.split_once("] "))
+            .ok_or_else(|| eyre!("Expected a line like `[1518-11-01 00:05] falls asleep`"))?;
         let time = PrimitiveDateTime::parse(
-            &time[1..17],
+            time,
             format_description!("[year]-[month]-[day] [hour]:[minute]"),
         )?;
-        Ok(match &action[1..6] {
-            "Guard" => {
-                let guard = action[8..]
-                    .split_ascii_whitespace()
-                    .next()
-                    .unwrap()
-                    .parse()?;
-                Event::ShiftStart { time, guard }
-            }
-            "falls" => Event::FellAsleep { time },
-            "wakes" => Event::WokeUp { time },
-            action => bail!("Unrecognized action {:?}", action),
+        Ok(if let Some(guard) = action.strip_prefix("Guard #") {
+            let guard = guard
+                .split_ascii_whitespace()
+                .next()
+                .unwrap_or_default()
+                .parse()?;
+            Event::ShiftStart { time, guard }
+        } else if action.starts_with("falls") {
+            Event::FellAsleep { time }
+        } else if action.starts_with("wakes") {
+            Event::WokeUp { time }
+        } else {
+            bail!("Unrecognized action {:?}", action)
         })
     }
 }
 
-fn parse(input: &str) -> Result<AHashMap<u32, Vec<Range<u8>>>> {
-    let mut events: Vec<Event> = input
-        .lines()
-        .map(|line| line.parse())
-        .collect::<Result<_, _>>()?;
-    events.sort_unstable_by_key(|event| event.time());
-    let mut events = events.into_iter();
+/// Parses an unordered guard log into the minutes each guard was asleep,
+/// keyed by guard id.
+///
+/// ```
+/// let log = "\
+///     [1518-11-01 00:30] falls asleep\n\
+///     [1518-11-01 00:00] Guard #10 begins shift\n\
+///     [1518-11-01 00:55] wakes up\n\
+/// ";
+/// let guards = aoc2018::day04::parse(log).unwrap();
+/// assert_eq!(vec![30..55], guards[&10]);
+/// ```
+pub fn parse(input: &str) -> Result<HashMap<u32, Vec<Range<u8>>>> {
+    let mut events = parse_events(input)?.into_iter();
 
-    let mut guards = AHashMap::<_, Vec<_>>::new();
+    let mut guards = HashMap::<_, Vec<_>>::default();
     let mut guard = match events.next() {
         Some(Event::ShiftStart { guard, .. }) => guard,
         _ => bail!("First event must be a shift start"),
@@ -89,38 +135,113 @@ fn parse(input: &str) -> Result<AHashMap<u32, Vec<Range<u8>>>> {
     Ok(guards)
 }
 
-#[instrument(skip(input))]
-fn part1(input: &str) -> Result<String> {
+/// Parses every line of the log, sorted by time.
+fn parse_events(input: &str) -> Result<Vec<Event>> {
+    let mut events: Vec<Event> = input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| line.parse().wrap_err_with(|| format!("Line {}", i + 1)))
+        .collect::<Result<_>>()?;
+    events.sort_unstable_by_key(|event| event.time());
+    Ok(events)
+}
+
+/// Counts the guards and shifts, and finds the dates the log covers.
+fn inspect(input: &str) -> Result<Vec<(&'static str, Fact)>> {
+    // Parsing the sleeps checks that the log is consistent.
+    let sleepers = parse(input)?.len();
+    let events = parse_events(input)?;
+    let guards: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::ShiftStart { guard, .. } => Some(guard),
+            _ => None,
+        })
+        .collect();
+    let shifts = guards.len();
+    let guards = guards.into_iter().collect::<HashSet<_>>().len();
+    let date = |event: Option<&Event>| event.map(|event| event.time().date().to_string());
+    Ok(vec![
+        ("events", events.len().into()),
+        ("shifts", shifts.into()),
+        ("guards", guards.into()),
+        ("guards who slept", sleepers.into()),
+        (
+            "first date",
+            date(events.first()).unwrap_or_default().into(),
+        ),
+        ("last date", date(events.last()).unwrap_or_default().into()),
+    ])
+}
+
+/// The minute `times` most often cover and how many cover it, choosing the
+/// earliest minute if several are tied.
+fn sleepiest_minute(times: &[Range<u8>]) -> (u8, usize) {
+    (0..60)
+        .map(|minute| {
+            let count = times.iter().filter(|range| range.contains(&minute)).count();
+            (minute, count)
+        })
+        .max_by_key(|&(minute, count)| (count, Reverse(minute)))
+        .unwrap()
+}
+
+/// Picks the guard who slept the most minutes, the lowest numbered one if
+/// several are tied, and their sleepiest minute.
+#[instrument(skip(input, ctx))]
+fn part1(input: &str, ctx: &Context) -> Result<String> {
     let guards = parse(input)?;
     let (guard, times) = guards
         .into_iter()
-        .max_by_key(|(_, times)| times.iter().map(|range| range.len()).sum::<usize>())
+        .max_by_key(|(guard, times)| {
+            let asleep: usize = times.iter().map(|range| range.len()).sum();
+            (asleep, Reverse(*guard))
+        })
         .ok_or_else(|| eyre!("No events?"))?;
-    let minute: u32 = (0..60)
-        .max_by_key(|minute| times.iter().filter(|range| range.contains(minute)).count())
-        .unwrap()
-        .into();
+    let minute = u32::from(sleepiest_minute(&times).0);
+    ctx.explain("guard", guard);
+    ctx.explain(
+        "minutes asleep",
+        times.iter().map(|range| range.len()).sum::<usize>(),
+    );
+    ctx.explain("minute", minute);
     Ok((guard * minute).to_string())
 }
 
-#[instrument(skip(input))]
-fn part2(input: &str) -> Result<String> {
+/// Picks the guard most often asleep on the same minute, the lowest numbered
+/// one if several are tied.
+#[instrument(skip(input, ctx))]
+fn part2(input: &str, ctx: &Context) -> Result<String> {
     let guards = parse(input)?;
-    let (guard, minute, _) = guards
+    let (guard, minute, count) = guards
         .into_iter()
         .map(|(guard, times)| {
-            let (minute, count) = (0..60)
-                .map(|minute| {
-                    (
-                        minute,
-                        times.iter().filter(|range| range.contains(&minute)).count(),
-                    )
-                })
-                .max_by_key(|&(_, count)| count)
-                .unwrap();
+            let (minute, count) = sleepiest_minute(&times);
             (guard, minute, count)
         })
-        .max_by_key(|&(_, _, count)| count)
-        .unwrap();
+        .max_by_key(|&(guard, _, count)| (count, Reverse(guard)))
+        .ok_or_else(|| eyre!("No events?"))?;
+    ctx.explain("guard", guard);
+    ctx.explain("minute", minute);
+    ctx.explain("times asleep", count);
     Ok((guard * u32::from(minute)).to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ties_pick_lowest_guard_and_earliest_minute() {
+        let log = "\
+            [1518-11-01 00:00] Guard #99 begins shift\n\
+            [1518-11-01 00:10] falls asleep\n\
+            [1518-11-01 00:12] wakes up\n\
+            [1518-11-02 00:00] Guard #10 begins shift\n\
+            [1518-11-02 00:20] falls asleep\n\
+            [1518-11-02 00:22] wakes up\n\
+        ";
+        assert_eq!("200", part1(log, &Context::default()).unwrap());
+        assert_eq!("200", part2(log, &Context::default()).unwrap());
+    }
+}
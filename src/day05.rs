@@ -1,64 +1,165 @@
+use std::io::BufRead;
+
 use eyre::Result;
 use tracing::{debug, instrument};
 
-use crate::Solution;
+use crate::{input, Context, Solution, Variant};
 
 pub const SOLUTION: Solution = Solution {
     input: include_str!("../input/day05.txt"),
-    part1,
-    part2,
+    part1: &[Variant::new("default", part1).with_stream(part1_stream)],
+    part2: &[
+        Variant::new("default", part2).with_stream(part2_stream),
+        Variant::new("naive", part2_naive),
+    ],
+    #[cfg(feature = "render")]
+    render: None,
+    #[cfg(feature = "animate")]
+    animate: None,
+    inspect: None,
+    params: &[],
+    examples: &[],
 };
 
+/// Repeatedly removes adjacent units of the same type and opposite polarity,
+/// returning the units left over. Units are ASCII letters, and any other
+/// bytes are ignored.
+///
+/// ```
+/// let reacted = aoc2018::day05::react("aAbBCcD".bytes());
+/// assert_eq!("D", String::from_utf8(reacted).unwrap());
+/// ```
 #[instrument(skip(input))]
-fn react(input: impl IntoIterator<Item = char>) -> Vec<char> {
+pub fn react(input: impl IntoIterator<Item = u8>) -> Vec<u8> {
     let input = input.into_iter();
-    let mut unreacted: Vec<char> = Vec::with_capacity(input.size_hint().0);
-    for c in input {
-        if !c.is_alphabetic() {
+    let mut unreacted: Vec<u8> = Vec::with_capacity(input.size_hint().0);
+    for b in input {
+        if !b.is_ascii_alphabetic() {
             continue;
         }
         match unreacted.last() {
-            Some(&last) if last.eq_ignore_ascii_case(&c) && last != c => {
+            Some(&last) if last.eq_ignore_ascii_case(&b) && last != b => {
                 unreacted.pop();
             }
-            _ => unreacted.push(c),
+            _ => unreacted.push(b),
         }
     }
-    debug!(unreacted = %unreacted.iter().copied().collect::<String>());
+    debug!(unreacted = %String::from_utf8_lossy(&unreacted));
     unreacted
 }
 
-#[instrument(skip(input))]
-fn part1(input: &str) -> Result<String> {
-    Ok(react(input.chars()).len().to_string())
+/// [`react`]s `input`, counting the reactions as `reactions`.
+fn react_counted(input: impl IntoIterator<Item = u8>, ctx: &Context) -> Vec<u8> {
+    let mut units = 0;
+    let reacted = react(
+        input
+            .into_iter()
+            .inspect(|b| units += u64::from(b.is_ascii_alphabetic())),
+    );
+    // Every reaction removes two units.
+    ctx.count("reactions", (units - reacted.len() as u64) / 2);
+    reacted
 }
 
-#[instrument(skip(input))]
-fn part2(input: &str) -> Result<String> {
-    let reacted = react(input.chars());
-    let max = ('a'..='z')
-        .into_iter()
+/// [`react_counted`]s the polymer in `reader` as it is read.
+fn react_stream(reader: &mut dyn BufRead, ctx: &Context) -> Result<Vec<u8>> {
+    let mut error = None;
+    let bytes = input::chars(reader)?
+        .map_while(|c| c.map_err(|e| error = Some(e)).ok())
+        .filter_map(|c| u8::try_from(c).ok());
+    let reacted = react_counted(bytes, ctx);
+    match error {
+        Some(error) => Err(error.into()),
+        None => Ok(reacted),
+    }
+}
+
+#[instrument(skip(input, ctx))]
+fn part1(input: &str, ctx: &Context) -> Result<String> {
+    Ok(react_counted(input.bytes(), ctx).len().to_string())
+}
+
+#[instrument(skip(reader, ctx))]
+fn part1_stream(reader: &mut dyn BufRead, ctx: &Context) -> Result<String> {
+    Ok(react_stream(reader, ctx)?.len().to_string())
+}
+
+#[instrument(skip(input, ctx))]
+fn part2(input: &str, ctx: &Context) -> Result<String> {
+    shortest_without_a_unit(react_counted(input.bytes(), ctx), ctx)
+}
+
+/// Only the reacted polymer is kept in memory, since removing a unit from it
+/// gives the same result as removing it from the original.
+#[instrument(skip(reader, ctx))]
+fn part2_stream(reader: &mut dyn BufRead, ctx: &Context) -> Result<String> {
+    shortest_without_a_unit(react_stream(reader, ctx)?, ctx)
+}
+
+/// The length of the shortest polymer left by removing one type of unit from
+/// the already `reacted` polymer and reacting the rest.
+fn shortest_without_a_unit(reacted: Vec<u8>, ctx: &Context) -> Result<String> {
+    let (unit, min) = (b'a'..=b'z')
         .map(|s| {
-            react(
+            let len = react_counted(
                 reacted
                     .iter()
                     .copied()
-                    .filter(|c| c.to_ascii_lowercase() != s),
+                    .filter(|b| b.to_ascii_lowercase() != s),
+                ctx,
             )
-            .len()
+            .len();
+            (s, len)
         })
-        .min()
+        .min_by_key(|&(_, len)| len)
         .unwrap();
-    Ok(max.to_string())
+    ctx.explain("reacted length", reacted.len());
+    ctx.explain("unit removed", char::from(unit));
+    Ok(min.to_string())
+}
+
+/// Reacts the whole original polymer once per removed unit, rather than
+/// starting from the already reacted polymer.
+#[instrument(skip(input, ctx))]
+fn part2_naive(input: &str, ctx: &Context) -> Result<String> {
+    let (unit, min) = (b'a'..=b'z')
+        .map(|s| {
+            let filtered = input.bytes().filter(|b| b.to_ascii_lowercase() != s);
+            (s, react_counted(filtered, ctx).len())
+        })
+        .min_by_key(|&(_, len)| len)
+        .unwrap();
+    ctx.explain("unit removed", char::from(unit));
+    Ok(min.to_string())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::{assert_variants_agree, Rng};
 
     #[test]
     fn fully_reacts() {
-        let reacted = react("dabAcCaCBAcCcaDA".chars());
-        assert_eq!("dabCBAcaDA", reacted.into_iter().collect::<String>())
+        let reacted = react("dabAcCaCBAcCcaDA".bytes());
+        assert_eq!(b"dabCBAcaDA", reacted.as_slice())
+    }
+
+    #[test]
+    fn variants_agree() {
+        let mut rng = Rng::new(5);
+        for _ in 0..20 {
+            let len = rng.range(0..500);
+            let polymer: String = (0..len)
+                .map(|_| {
+                    let unit = char::from(b'a' + rng.range(0..4) as u8);
+                    if rng.range(0..2) == 0 {
+                        unit.to_ascii_uppercase()
+                    } else {
+                        unit
+                    }
+                })
+                .collect();
+            assert_variants_agree(SOLUTION.part2, &polymer);
+        }
     }
 }
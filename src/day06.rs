@@ -1,26 +1,65 @@
 use std::cmp::Ordering;
 
-use ahash::AHashSet;
 use eyre::{eyre, Result};
 use itertools::Itertools;
 use tracing::instrument;
 
-use crate::Solution;
+#[cfg(feature = "render")]
+use crate::render::{palette, Image, Rgb};
+use crate::{
+    grid::{Grid, Point},
+    hash::HashSet,
+    Context, Example, Param, Solution, Variant,
+};
 
 pub const SOLUTION: Solution = Solution {
     input: include_str!("../input/day06.txt"),
-    part1,
-    part2,
+    part1: &[Variant::new("default", part1)],
+    part2: &[Variant::new("default", part2)],
+    #[cfg(feature = "render")]
+    render: Some(render),
+    #[cfg(feature = "animate")]
+    animate: None,
+    inspect: None,
+    params: &[THRESHOLD],
+    examples: &[Example {
+        input: EXAMPLE,
+        params: &[("threshold", 32)],
+        answers: [Some("17"), Some("16")],
+    }],
 };
 
-fn parse(input: &str) -> Result<Vec<(usize, usize)>> {
+/// The example from the puzzle statement.
+const EXAMPLE: &str = "\
+    1, 1\n\
+    1, 6\n\
+    8, 3\n\
+    3, 4\n\
+    5, 5\n\
+    8, 9\n\
+";
+
+const THRESHOLD: Param = Param::new(
+    "threshold",
+    10000,
+    "Total distance to every point that a safe location must be under",
+);
+
+/// Parses one `x, y` coordinate per line.
+///
+/// ```
+/// use aoc2018::{day06::parse, grid::Point};
+///
+/// assert_eq!(vec![Point::new(1, 1), Point::new(8, 3)], parse("1, 1\n8, 3").unwrap());
+/// ```
+pub fn parse(input: &str) -> Result<Vec<Point>> {
     input
         .lines()
         .map(|line| {
             let (x, y) = line
                 .split_once(',')
                 .ok_or_else(|| eyre!("No comma found on line: {:?}", line))?;
-            Ok((x.trim().parse()?, y.trim().parse()?))
+            Ok(Point::new(x.trim().parse()?, y.trim().parse()?))
         })
         .collect()
 }
@@ -40,59 +79,75 @@ impl Default for Cell {
     }
 }
 
-#[instrument(skip(input))]
-fn part1(input: &str) -> Result<String> {
-    let points = parse(input)?;
-    let max_x = points.iter().map(|&(x, _)| x).max().unwrap();
-    let max_y = points.iter().map(|&(_, y)| y).max().unwrap();
-    let mut grid = vec![vec![Cell::default(); max_x + 1]; max_y + 1];
-    for (point, (x, y)) in points.into_iter().enumerate() {
-        for (j, row) in grid.iter_mut().enumerate() {
-            for (i, cell) in row.iter_mut().enumerate() {
-                let dist = x.abs_diff(i) + y.abs_diff(j);
-                match dist.cmp(&cell.score) {
-                    Ordering::Less => {
-                        cell.score = dist;
-                        cell.owner = Some(point);
-                    }
-                    Ordering::Equal => {
-                        cell.owner = None;
-                    }
-                    Ordering::Greater => {}
+/// The index of the point closest to each cell of the bounding box of
+/// `points`, or `None` where two or more points tie.
+pub fn closest_points(points: &[Point]) -> Grid<Option<usize>> {
+    let width = points.iter().map(|point| point.x + 1).max().unwrap_or(0);
+    let height = points.iter().map(|point| point.y + 1).max().unwrap_or(0);
+    let mut grid = Grid::new(width, height, Cell::default());
+    for (owner, &point) in points.iter().enumerate() {
+        for (location, cell) in grid.iter_mut() {
+            let dist = point.manhattan_distance(location);
+            match dist.cmp(&cell.score) {
+                Ordering::Less => {
+                    cell.score = dist;
+                    cell.owner = Some(owner);
+                }
+                Ordering::Equal => {
+                    cell.owner = None;
                 }
+                Ordering::Greater => {}
             }
         }
     }
+    grid.map(|_, cell| cell.owner)
+}
 
-    let mut infinite = AHashSet::new();
-    infinite.extend(grid[0].iter().filter_map(|cell| cell.owner));
-    infinite.extend(grid.iter().filter_map(|row| row[0].owner));
-    infinite.extend(grid.iter().filter_map(|row| row[max_x].owner));
-    infinite.extend(grid[max_y].iter().filter_map(|cell| cell.owner));
+/// Draws the area closest to each point in its own colour, with the points
+/// themselves in black.
+#[cfg(feature = "render")]
+fn render(input: &str, _ctx: &Context) -> Result<Image> {
+    let points = parse(input)?;
+    let mut image = closest_points(&points).map(|_, owner| match owner {
+        Some(owner) => palette::owner(*owner),
+        None => palette::UNOWNED,
+    });
+    for &point in &points {
+        image[point] = Rgb::BLACK;
+    }
+    Ok(image)
+}
 
+/// The size of the largest finite area. Which point owns it does not matter,
+/// so ties between points need no rule.
+#[instrument(skip(input, _ctx))]
+fn part1(input: &str, _ctx: &Context) -> Result<String> {
+    let grid = closest_points(&parse(input)?);
+    let infinite: HashSet<_> = grid.border().filter_map(|(_, &owner)| owner).collect();
     let counts = grid
-        .into_iter()
-        .flatten()
-        .filter_map(|cell| cell.owner)
-        .filter(|point| !infinite.contains(point))
+        .iter()
+        .filter_map(|(_, &owner)| owner)
+        .filter(|owner| !infinite.contains(owner))
         .counts();
     Ok(counts.values().max().unwrap().to_string())
 }
 
-#[instrument(skip(input))]
-fn part2(input: &str) -> Result<String> {
+/// Counts the locations whose total distance to every point is less than
+/// [`THRESHOLD`].
+#[instrument(skip(input, ctx))]
+fn part2(input: &str, ctx: &Context) -> Result<String> {
+    let threshold: usize = ctx.param(&THRESHOLD)?;
     let points = parse(input)?;
-    let max_x = points.iter().map(|&(x, _)| x).max().unwrap();
-    let max_y = points.iter().map(|&(_, y)| y).max().unwrap();
+    let max_x = points.iter().map(|point| point.x).max().unwrap();
+    let max_y = points.iter().map(|point| point.y).max().unwrap();
     let mut safe_points = 0;
     for x in 0..max_x {
         for y in 0..max_y {
             let total_distance: usize = points
                 .iter()
-                .copied()
-                .map(|(i, j)| i.abs_diff(x) + j.abs_diff(y))
+                .map(|point| point.manhattan_distance(Point::new(x, y)))
                 .sum();
-            if total_distance < 10000 {
+            if total_distance < threshold {
                 safe_points += 1;
             }
         }
@@ -106,7 +161,6 @@ mod tests {
 
     #[test]
     fn largest_area() {
-        let input = "1, 1\n1, 6\n8, 3\n3, 4\n5, 5\n8, 9";
-        assert_eq!("17", &part1(input).unwrap());
+        assert_eq!("17", &part1(EXAMPLE, &Context::default()).unwrap());
     }
 }
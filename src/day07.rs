@@ -1,56 +1,103 @@
 use std::{cmp::Reverse, collections::BinaryHeap};
 
-use ahash::{AHashMap, AHashSet};
-use eyre::{Result, WrapErr};
-use recap::Recap;
-use serde::Deserialize;
+use eyre::{bail, Result};
 use tracing::instrument;
 
-use crate::Solution;
+use crate::{
+    hash::{HashMap, HashSet},
+    parse::{self, char_if, literal, PResult},
+    Context, Example, Fact, Param, Solution, Variant,
+};
 
 pub const SOLUTION: Solution = Solution {
     input: include_str!("../input/day07.txt"),
-    part1,
-    part2,
+    part1: &[Variant::new("default", part1)],
+    part2: &[Variant::new("default", part2)],
+    #[cfg(feature = "render")]
+    render: None,
+    #[cfg(feature = "animate")]
+    animate: None,
+    inspect: None,
+    params: &[WORKERS, BASE_TIME],
+    examples: &[Example {
+        input: EXAMPLE,
+        params: &[("workers", 2), ("base_time", 0)],
+        answers: [Some("CABDFE"), Some("15")],
+    }],
 };
 
-#[derive(Debug)]
-struct Graph {
-    forward: AHashMap<char, AHashSet<char>>,
-    backward: AHashMap<char, AHashSet<char>>,
+/// The example from the puzzle statement.
+const EXAMPLE: &str = "\
+    Step C must be finished before step A can begin.\n\
+    Step C must be finished before step F can begin.\n\
+    Step A must be finished before step B can begin.\n\
+    Step A must be finished before step D can begin.\n\
+    Step B must be finished before step E can begin.\n\
+    Step D must be finished before step E can begin.\n\
+    Step F must be finished before step E can begin.\n\
+";
+
+const WORKERS: Param = Param::new("workers", 5, "How many workers build steps at once").at_least(1);
+
+const BASE_TIME: Param = Param::new(
+    "base_time",
+    60,
+    "Seconds every step takes on top of its letter",
+)
+.at_least(0)
+.at_most(u32::MAX as i64 - 26);
+
+/// The steps of the sleigh's instructions and the dependencies between them.
+#[derive(Debug, Clone)]
+pub struct Graph {
+    forward: HashMap<char, HashSet<char>>,
+    backward: HashMap<char, HashSet<char>>,
 }
 
-fn parse(input: &str) -> Result<Graph> {
-    let mut forward: AHashMap<char, AHashSet<char>> = AHashMap::new();
-    let mut backward: AHashMap<char, AHashSet<char>> = AHashMap::new();
-    for line in input.lines() {
-        #[derive(Debug, Deserialize, Recap)]
-        #[recap(
-            regex = r#"Step (?P<dependency>[A-Z]) must be finished before step (?P<dependant>[A-Z]) can begin."#
-        )]
-        struct Line {
-            dependant: char,
-            dependency: char,
-        }
-        let line: Line = line.parse().wrap_err_with(|| line.to_owned())?;
-        forward
-            .entry(line.dependency)
-            .or_default()
-            .insert(line.dependant);
-        forward.entry(line.dependant).or_default();
-        backward
-            .entry(line.dependant)
-            .or_default()
-            .insert(line.dependency);
-        backward.entry(line.dependency).or_default();
+/// Parses lines like `Step C must be finished before step A can begin.`
+pub fn parse(input: &str) -> Result<Graph> {
+    let mut forward: HashMap<char, HashSet<char>> = HashMap::default();
+    let mut backward: HashMap<char, HashSet<char>> = HashMap::default();
+    for (dependency, dependant) in parse::lines(input, dependency)? {
+        forward.entry(dependency).or_default().insert(dependant);
+        forward.entry(dependant).or_default();
+        backward.entry(dependant).or_default().insert(dependency);
+        backward.entry(dependency).or_default();
     }
     Ok(Graph { forward, backward })
 }
 
-#[instrument(skip(input))]
-fn part1(input: &str) -> Result<String> {
-    let mut graph = parse(input)?;
+/// A step and the step that depends on it.
+fn dependency(input: &str) -> PResult<'_, (char, char)> {
+    let step = char_if("a step letter", |c| c.is_ascii_uppercase());
+    let (input, ()) = literal("Step ")(input)?;
+    let (input, dependency) = step(input)?;
+    let (input, ()) = literal(" must be finished before step ")(input)?;
+    let (input, dependant) = step(input)?;
+    let (input, ()) = literal(" can begin.")(input)?;
+    Ok((input, (dependency, dependant)))
+}
+
+#[instrument(skip(input, _ctx))]
+fn part1(input: &str, _ctx: &Context) -> Result<String> {
+    Ok(step_order(parse(input)?))
+}
 
+/// The order a single worker completes the steps in, always picking the
+/// alphabetically first step that is ready.
+///
+/// ```
+/// use aoc2018::day07::{parse, step_order};
+///
+/// let graph = parse(
+///     "Step C must be finished before step A can begin.\n\
+///      Step A must be finished before step B can begin.\n\
+///      Step C must be finished before step B can begin.",
+/// )
+/// .unwrap();
+/// assert_eq!("CAB", step_order(graph));
+/// ```
+pub fn step_order(mut graph: Graph) -> String {
     let mut queue: BinaryHeap<_> = graph
         .backward
         .iter()
@@ -71,13 +118,28 @@ fn part1(input: &str) -> Result<String> {
             }
         }
     }
-    Ok(answer)
+    answer
 }
 
-#[instrument(skip(input))]
-fn part2(input: &str) -> Result<String> {
+#[instrument(skip(input, ctx))]
+fn part2(input: &str, ctx: &Context) -> Result<String> {
     let graph = parse(input)?;
-    construction_time(graph, 5, 60).map(|time| time.to_string())
+    let workers = ctx.param(&WORKERS)?;
+    let base_time = ctx.param(&BASE_TIME)?;
+    let schedule = construction_schedule(graph, workers, base_time)?;
+    let time = schedule.iter().map(|job| job.end).max().unwrap_or(0);
+    if ctx.explaining() {
+        let jobs = schedule.iter().map(|job| {
+            Fact::table([
+                ("step", Fact::from(job.step)),
+                ("worker", job.worker.into()),
+                ("start", job.start.into()),
+                ("end", job.end.into()),
+            ])
+        });
+        ctx.explain("schedule", jobs.collect::<Vec<_>>());
+    }
+    Ok(time.to_string())
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -87,7 +149,43 @@ struct Task {
     name: char,
 }
 
-fn construction_time(mut graph: Graph, workers: usize, base_time: u32) -> Result<u32> {
+/// How long `workers` elves take to complete every step, where step `A` takes
+/// `base_time + 1` seconds, `B` takes `base_time + 2` and so on.
+///
+/// ```
+/// use aoc2018::day07::{construction_time, parse};
+///
+/// let graph = parse(
+///     "Step C must be finished before step A can begin.\n\
+///      Step C must be finished before step B can begin.",
+/// )
+/// .unwrap();
+/// assert_eq!(5, construction_time(graph, 2, 0).unwrap());
+/// ```
+pub fn construction_time(graph: Graph, workers: usize, base_time: u32) -> Result<u32> {
+    let schedule = construction_schedule(graph, workers, base_time)?;
+    Ok(schedule.iter().map(|job| job.end).max().unwrap_or(0))
+}
+
+/// One step of the construction, as carried out by one worker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Job {
+    pub step: char,
+    pub worker: usize,
+    pub start: u32,
+    pub end: u32,
+}
+
+/// Which worker does each step and when, in the order the steps are started.
+/// See [`construction_time`].
+///
+/// Steps that become ready at the same time are started alphabetically, each
+/// by the worker that has been free the longest, the lowest numbered if
+/// several have.
+pub fn construction_schedule(mut graph: Graph, workers: usize, base_time: u32) -> Result<Vec<Job>> {
+    if workers == 0 {
+        bail!("At least one worker is needed");
+    }
     let task_time = move |c: char| {
         if c == '#' {
             return 0;
@@ -112,16 +210,26 @@ fn construction_time(mut graph: Graph, workers: usize, base_time: u32) -> Result
         completion_time: 0,
         name: '#',
     }));
-    let mut workers: BinaryHeap<_> = std::iter::repeat(Reverse(0u32)).take(workers).collect();
+    let mut workers: BinaryHeap<_> = (0..workers).map(|worker| Reverse((0u32, worker))).collect();
+    let mut schedule = Vec::new();
 
     while let Some(Reverse(Task {
         completion_time,
         name: next,
     })) = queue.pop()
     {
-        let Reverse(worker) = workers.pop().expect("there are no workers");
-        let completion_time = std::cmp::max(completion_time, worker) + task_time(next);
-        workers.push(Reverse(completion_time));
+        let Reverse((free_at, worker)) = workers.pop().expect("workers are put back");
+        let start = std::cmp::max(completion_time, free_at);
+        let completion_time = start + task_time(next);
+        workers.push(Reverse((completion_time, worker)));
+        if next != '#' {
+            schedule.push(Job {
+                step: next,
+                worker,
+                start,
+                end: completion_time,
+            });
+        }
         for dependant in graph.forward.remove(&next).into_iter().flatten() {
             let dependencies = graph.backward.get_mut(&dependant).unwrap();
             dependencies.remove(&next);
@@ -134,32 +242,43 @@ fn construction_time(mut graph: Graph, workers: usize, base_time: u32) -> Result
             }
         }
     }
-    Ok(workers
-        .into_iter()
-        .map(|Reverse(time)| time)
-        .max()
-        .expect("there are no workers"))
+    Ok(schedule)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const INPUT: &str = r#"Step C must be finished before step A can begin.
-Step C must be finished before step F can begin.
-Step A must be finished before step B can begin.
-Step A must be finished before step D can begin.
-Step B must be finished before step E can begin.
-Step D must be finished before step E can begin.
-Step F must be finished before step E can begin."#;
-
     #[test]
     fn test_part1() {
-        assert_eq!("CABDFE", &part1(INPUT).unwrap());
+        assert_eq!("CABDFE", &part1(EXAMPLE, &Context::default()).unwrap());
     }
 
     #[test]
     fn test_construction_time() {
-        assert_eq!(15, construction_time(parse(INPUT).unwrap(), 2, 0).unwrap())
+        assert_eq!(
+            15,
+            construction_time(parse(EXAMPLE).unwrap(), 2, 0).unwrap()
+        )
+    }
+
+    #[test]
+    fn schedule() {
+        let schedule = construction_schedule(parse(EXAMPLE).unwrap(), 2, 0).unwrap();
+        let times: Vec<_> = schedule
+            .iter()
+            .map(|job| (job.step, job.start, job.end))
+            .collect();
+        assert_eq!(
+            vec![
+                ('C', 0, 3),
+                ('A', 3, 4),
+                ('F', 3, 9),
+                ('B', 4, 6),
+                ('D', 6, 10),
+                ('E', 10, 15)
+            ],
+            times
+        );
     }
 }
@@ -1,121 +1,322 @@
+use std::{io::BufRead, iter};
+
 use eyre::{bail, eyre, Result};
 use smallvec::SmallVec;
 use tracing::instrument;
 
-use crate::Solution;
+use crate::{input, Context, Fact, Solution, Variant};
 
 pub const SOLUTION: Solution = Solution {
     input: include_str!("../input/day08.txt"),
-    part1,
-    part2,
+    part1: &[Variant::new("default", part1).with_stream(part1_stream)],
+    part2: &[Variant::new("default", part2).with_stream(part2_stream)],
+    #[cfg(feature = "render")]
+    render: None,
+    #[cfg(feature = "animate")]
+    animate: None,
+    inspect: Some(inspect),
+    params: &[],
+    examples: &[],
 };
 
-fn parse<R: Reduce>(input: &str, reducer: R) -> Result<R::Output> {
+/// Walks the licence tree encoded in `input`, folding each node with `reducer`.
+///
+/// The tree is never built in memory: each node is reduced as soon as its
+/// children and metadata have been read. Nor is it walked recursively, so
+/// however deeply it nests only the path to the current node is kept.
+///
+/// ```
+/// use aoc2018::day08::{parse, Reduce};
+///
+/// /// Counts the nodes in the tree.
+/// struct Count(usize);
+/// impl Reduce for Count {
+///     type Output = usize;
+///
+///     fn child(&mut self) -> Option<Self> {
+///         Some(Count(0))
+///     }
+///
+///     fn add_child(&mut self, nodes: usize) -> eyre::Result<()> {
+///         self.0 += nodes;
+///         Ok(())
+///     }
+///
+///     fn metadata(self, _metadata: &[u32]) -> eyre::Result<usize> {
+///         Ok(self.0 + 1)
+///     }
+/// }
+///
+/// let nodes = parse("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2", Count(0)).unwrap();
+/// assert_eq!(4, nodes);
+/// ```
+pub fn parse<R: Reduce>(input: &str, reducer: R) -> Result<R::Output> {
     let mut input = input
         .split_ascii_whitespace()
         .map(move |num| -> Result<u32> { Ok(num.parse::<u32>()?) });
     reduce(&mut input, reducer)
 }
 
+/// Like [`parse`], but reads the numbers from `reader` as they are needed.
+///
+/// ```
+/// use aoc2018::day08::parse_stream;
+///
+/// let input = "0 3 1 2 3\n".as_bytes();
+/// assert_eq!((), parse_stream(input, ()).unwrap());
+/// assert!(parse_stream("0 3 1 2".as_bytes(), ()).is_err());
+/// ```
+pub fn parse_stream<R: Reduce>(reader: impl BufRead, reducer: R) -> Result<R::Output> {
+    let mut chars = input::chars(reader)?;
+    let mut numbers = iter::from_fn(move || {
+        let mut number: Option<u32> = None;
+        for c in chars.by_ref() {
+            let c = match c {
+                Ok(c) => c,
+                Err(error) => return Some(Err(error.into())),
+            };
+            match c.to_digit(10) {
+                Some(digit) => {
+                    number = number
+                        .unwrap_or(0)
+                        .checked_mul(10)
+                        .and_then(|n| n.checked_add(digit));
+                    if number.is_none() {
+                        return Some(Err(eyre!("Number too large")));
+                    }
+                }
+                None if c.is_whitespace() => {
+                    if number.is_some() {
+                        return number.map(Ok);
+                    }
+                }
+                None => return Some(Err(eyre!("Invalid character {:?} in a number", c))),
+            }
+        }
+        number.map(Ok)
+    });
+    reduce(&mut numbers, reducer)
+}
+
+/// A node whose children are still being read.
+struct Node<R> {
+    /// `None` if the node's subtree is being skipped.
+    reducer: Option<R>,
+    children_left: u32,
+    metadata_len: usize,
+}
+
 fn reduce<I: Iterator<Item = Result<u32>>, R: Reduce>(
     input: &mut I,
-    mut reducer: R,
+    reducer: R,
 ) -> Result<R::Output> {
-    let children = input
-        .next()
-        .ok_or(eyre!("Missing number of child nodes"))??;
-    let metadata_len = input
-        .next()
-        .ok_or(eyre!("Missing number of metadata entries"))?? as usize;
-    for _ in 0..children {
-        let mut called = false;
-        reducer.child(|child_reducer| {
-            called = true;
-            reduce(input, child_reducer)
-        })?;
-        if !called {
-            reduce(input, ())?;
+    // The nodes from the root to the one being read.
+    let mut path: Vec<Node<R>> = Vec::new();
+    let mut metadata = Vec::new();
+    let mut next = Some(reducer);
+    loop {
+        let children_left = input
+            .next()
+            .ok_or(eyre!("Missing number of child nodes"))??;
+        let metadata_len = input
+            .next()
+            .ok_or(eyre!("Missing number of metadata entries"))??
+            as usize;
+        path.push(Node {
+            reducer: next.take(),
+            children_left,
+            metadata_len,
+        });
+        // Finish every node that has no children left to read, until one
+        // does and its next child is read.
+        while let Some(node) = path.last_mut() {
+            if node.children_left > 0 {
+                node.children_left -= 1;
+                next = node.reducer.as_mut().and_then(R::child);
+                break;
+            }
+            let node = path.pop().expect("the path is not empty");
+            metadata.clear();
+            for datum in input.by_ref().take(node.metadata_len) {
+                metadata.push(datum?);
+            }
+            if metadata.len() != node.metadata_len {
+                bail!(
+                    "Missing metadata: found {}, expected {}",
+                    metadata.len(),
+                    node.metadata_len
+                );
+            }
+            let output = match node.reducer {
+                Some(reducer) => Some(reducer.metadata(&metadata)?),
+                None => None,
+            };
+            match (path.last_mut(), output) {
+                (Some(parent), Some(output)) => parent
+                    .reducer
+                    .as_mut()
+                    .expect("only children of reduced nodes are reduced")
+                    .add_child(output)?,
+                (Some(_), None) => {}
+                (None, output) => return Ok(output.expect("the root is always reduced")),
+            }
         }
     }
-    let mut metadata = SmallVec::<[u32; 16]>::with_capacity(metadata_len);
-    for datum in input.take(metadata_len) {
-        metadata.push(datum?);
-    }
-    if metadata.len() != metadata_len {
-        bail!(
-            "Missing metadata: found {}, expected {}",
-            metadata.len(),
-            metadata_len
-        );
-    }
-    Ok(reducer.metadata(&metadata))
 }
 
-trait Reduce
+/// Folds a node of the licence tree into a value.
+pub trait Reduce
 where
     Self: Sized,
 {
     type Output;
 
-    fn child(&mut self, child: impl FnOnce(Self) -> Result<Self::Output>) -> Result<()>;
-    fn metadata(self, metadata: &[u32]) -> Self::Output;
+    /// Called before each child is read, in order. Returns a fresh reducer
+    /// for that child, or `None` to skip the child's subtree.
+    fn child(&mut self) -> Option<Self>;
+    /// Called with the output of each child that was not skipped, in order.
+    fn add_child(&mut self, output: Self::Output) -> Result<()>;
+    /// Called after every child with this node's metadata entries.
+    fn metadata(self, metadata: &[u32]) -> Result<Self::Output>;
 }
 
 impl Reduce for () {
     type Output = ();
 
-    fn child(&mut self, child: impl FnOnce(Self) -> Result<Self::Output>) -> Result<()> {
-        child(())
+    fn child(&mut self) -> Option<Self> {
+        None
     }
 
-    fn metadata(self, _metadata: &[u32]) -> Self::Output {}
+    fn add_child(&mut self, _output: Self::Output) -> Result<()> {
+        Ok(())
+    }
+
+    fn metadata(self, _metadata: &[u32]) -> Result<Self::Output> {
+        Ok(())
+    }
 }
 
-#[instrument(skip(input))]
-fn part1(input: &str) -> Result<String> {
+/// Measures the licence tree.
+fn inspect(input: &str) -> Result<Vec<(&'static str, Fact)>> {
     #[derive(Default)]
-    struct SumReduce(u32);
-    impl Reduce for SumReduce {
-        type Output = u32;
+    struct Shape {
+        nodes: usize,
+        depth: usize,
+        metadata: usize,
+    }
+    impl Reduce for Shape {
+        type Output = Shape;
 
-        fn child(&mut self, child: impl FnOnce(Self) -> Result<Self::Output>) -> Result<()> {
-            self.0 += child(Self::default())?;
+        fn child(&mut self) -> Option<Self> {
+            Some(Shape::default())
+        }
+
+        fn add_child(&mut self, child: Shape) -> Result<()> {
+            self.nodes += child.nodes;
+            self.depth = self.depth.max(child.depth);
+            self.metadata += child.metadata;
             Ok(())
         }
 
-        fn metadata(self, metadata: &[u32]) -> Self::Output {
-            self.0 + metadata.iter().sum::<u32>()
+        fn metadata(self, metadata: &[u32]) -> Result<Shape> {
+            Ok(Shape {
+                nodes: self.nodes + 1,
+                depth: self.depth + 1,
+                metadata: self.metadata + metadata.len(),
+            })
         }
     }
-    parse(input, SumReduce::default()).map(|num| num.to_string())
+
+    let numbers = input.split_ascii_whitespace().count();
+    let shape = parse(input, Shape::default())?;
+    Ok(vec![
+        ("numbers", numbers.into()),
+        ("nodes", shape.nodes.into()),
+        ("depth", shape.depth.into()),
+        ("metadata entries", shape.metadata.into()),
+    ])
 }
 
-#[instrument(skip(input))]
-fn part2(input: &str) -> Result<String> {
-    #[derive(Default)]
-    struct ValueReduce(SmallVec<[u32; 16]>);
-    impl Reduce for ValueReduce {
-        type Output = u32;
+/// Adds up `values`, failing rather than overflowing on huge trees.
+fn checked_sum(values: impl IntoIterator<Item = u64>) -> Result<u64> {
+    values
+        .into_iter()
+        .try_fold(0u64, u64::checked_add)
+        .ok_or(eyre!("The sum is too large"))
+}
 
-        fn child(&mut self, child: impl FnOnce(Self) -> Result<Self::Output>) -> Result<()> {
-            self.0.push(child(Self::default())?);
-            Ok(())
-        }
+/// Sums every metadata entry in the tree.
+#[derive(Default)]
+struct SumReduce(u64);
 
-        fn metadata(self, metadata: &[u32]) -> Self::Output {
-            if self.0.is_empty() {
-                metadata.iter().sum()
-            } else {
-                metadata
-                    .iter()
-                    .map(|&datum| self.0.get(datum as usize - 1).copied().unwrap_or(0))
-                    .sum()
-            }
+impl Reduce for SumReduce {
+    type Output = u64;
+
+    fn child(&mut self) -> Option<Self> {
+        Some(Self::default())
+    }
+
+    fn add_child(&mut self, sum: u64) -> Result<()> {
+        self.0 = checked_sum([self.0, sum])?;
+        Ok(())
+    }
+
+    fn metadata(self, metadata: &[u32]) -> Result<u64> {
+        checked_sum(iter::once(self.0).chain(metadata.iter().map(|&datum| u64::from(datum))))
+    }
+}
+
+/// Finds the value of the root node, from its children's values.
+#[derive(Default)]
+struct ValueReduce(SmallVec<[u64; 16]>);
+
+impl Reduce for ValueReduce {
+    type Output = u64;
+
+    fn child(&mut self) -> Option<Self> {
+        Some(Self::default())
+    }
+
+    fn add_child(&mut self, value: u64) -> Result<()> {
+        self.0.push(value);
+        Ok(())
+    }
+
+    fn metadata(self, metadata: &[u32]) -> Result<u64> {
+        if self.0.is_empty() {
+            checked_sum(metadata.iter().map(|&datum| u64::from(datum)))
+        } else {
+            checked_sum(metadata.iter().map(|&datum| {
+                (datum as usize)
+                    .checked_sub(1)
+                    .and_then(|i| self.0.get(i))
+                    .copied()
+                    .unwrap_or(0)
+            }))
         }
     }
+}
+
+#[instrument(skip(input, _ctx))]
+fn part1(input: &str, _ctx: &Context) -> Result<String> {
+    parse(input, SumReduce::default()).map(|num| num.to_string())
+}
+
+#[instrument(skip(reader, _ctx))]
+fn part1_stream(reader: &mut dyn BufRead, _ctx: &Context) -> Result<String> {
+    parse_stream(reader, SumReduce::default()).map(|num| num.to_string())
+}
+
+#[instrument(skip(input, _ctx))]
+fn part2(input: &str, _ctx: &Context) -> Result<String> {
     parse(input, ValueReduce::default()).map(|num| num.to_string())
 }
 
+#[instrument(skip(reader, _ctx))]
+fn part2_stream(reader: &mut dyn BufRead, _ctx: &Context) -> Result<String> {
+    parse_stream(reader, ValueReduce::default()).map(|num| num.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,13 +325,41 @@ mod tests {
 
     #[test]
     fn sum() {
-        let sum = part1(INPUT).unwrap();
+        let sum = part1(INPUT, &Context::default()).unwrap();
         assert_eq!("138", &sum);
     }
 
     #[test]
     fn value() {
-        let value = part2(INPUT).unwrap();
+        let value = part2(INPUT, &Context::default()).unwrap();
         assert_eq!("66", &value);
     }
+
+    #[test]
+    fn shape() {
+        let facts = inspect(INPUT).unwrap();
+        assert_eq!(
+            vec![
+                ("numbers", Fact::Int(16)),
+                ("nodes", Fact::Int(4)),
+                ("depth", Fact::Int(3)),
+                ("metadata entries", Fact::Int(8)),
+            ],
+            facts
+        );
+        assert!(inspect("2 3 0 3 10").is_err());
+    }
+
+    #[test]
+    fn deep_and_large() {
+        // A chain of nodes far deeper than the stack could recurse through.
+        let depth = 20_000;
+        let mut input = "1 1 ".repeat(depth);
+        input.push_str("0 2 4294967295 4294967295");
+        input.push_str(&" 4294967295".repeat(depth));
+        let sum = 4_294_967_295 * (depth as u64 + 2);
+        assert_eq!(sum.to_string(), part1(&input, &Context::default()).unwrap());
+        let stream = part1_stream(&mut input.as_bytes(), &Context::default()).unwrap();
+        assert_eq!(sum.to_string(), stream);
+    }
 }
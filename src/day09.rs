@@ -1,38 +1,114 @@
-use std::collections::VecDeque;
+use std::{collections::VecDeque, str::FromStr};
 
-use eyre::Result;
-use recap::Recap;
-use serde::Deserialize;
+use eyre::{eyre, Result};
 use tracing::{debug, instrument};
 
-use crate::Solution;
+use crate::{
+    parse::{self, literal, uint, PResult},
+    Context, Example, Param, Solution, Variant,
+};
 
 pub const SOLUTION: Solution = Solution {
     input: include_str!("../input/day09.txt"),
-    part1,
-    part2,
+    part1: &[
+        Variant::new("vecdeque", part1),
+        Variant::new("linked-list", part1_linked_list),
+    ],
+    part2: &[
+        Variant::new("vecdeque", part2),
+        Variant::new("linked-list", part2_linked_list),
+    ],
+    #[cfg(feature = "render")]
+    render: None,
+    #[cfg(feature = "animate")]
+    animate: None,
+    inspect: None,
+    params: &[MULTIPLIER],
+    examples: &[Example {
+        input: EXAMPLE,
+        params: &[("multiplier", 1)],
+        answers: [Some("8317"), Some("8317")],
+    }],
 };
 
-#[derive(Debug, Clone, Copy, Deserialize, Recap)]
-#[recap(regex = r#"(?P<players>\d+) players; last marble is worth (?P<marbles>\d+) points"#)]
-struct Rules {
-    players: usize,
-    marbles: usize,
+/// The example from the puzzle statement.
+const EXAMPLE: &str = "\
+    10 players; last marble is worth 1618 points\n\
+";
+
+const MULTIPLIER: Param = Param::new(
+    "multiplier",
+    100,
+    "How many times more marbles part 2 plays with",
+)
+.at_least(1);
+
+/// The game's setup, parsed from `10 players; last marble is worth 1618 points`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    pub players: usize,
+    pub marbles: usize,
+}
+
+impl FromStr for Rules {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        parse::complete(s, rules)
+    }
+}
+
+fn rules(input: &str) -> PResult<'_, Rules> {
+    let (input, players) = uint(input)?;
+    let (input, ()) = literal(" players; last marble is worth ")(input)?;
+    let (input, marbles) = uint(input)?;
+    let (input, ()) = literal(" points")(input)?;
+    Ok((input, Rules { players, marbles }))
+}
+
+#[instrument(skip(input, ctx))]
+fn part1(input: &str, ctx: &Context) -> Result<String> {
+    Ok(max_score(input.parse()?, ctx).to_string())
 }
 
-#[instrument(skip(input))]
-fn part1(input: &str) -> Result<String> {
-    Ok(max_score(input.parse()?).to_string())
+#[instrument(skip(input, ctx))]
+fn part2(input: &str, ctx: &Context) -> Result<String> {
+    Ok(max_score(part2_rules(input, ctx)?, ctx).to_string())
 }
 
-#[instrument(skip(input))]
-fn part2(input: &str) -> Result<String> {
+#[instrument(skip(input, ctx))]
+fn part1_linked_list(input: &str, ctx: &Context) -> Result<String> {
+    Ok(max_score_linked_list(input.parse()?, ctx).to_string())
+}
+
+#[instrument(skip(input, ctx))]
+fn part2_linked_list(input: &str, ctx: &Context) -> Result<String> {
+    Ok(max_score_linked_list(part2_rules(input, ctx)?, ctx).to_string())
+}
+
+/// The rules with [`MULTIPLIER`] times as many marbles.
+fn part2_rules(input: &str, ctx: &Context) -> Result<Rules> {
     let mut rules: Rules = input.parse()?;
-    rules.marbles *= 100;
-    Ok(max_score(rules).to_string())
+    let multiplier: usize = ctx.param(&MULTIPLIER)?;
+    rules.marbles = rules
+        .marbles
+        .checked_mul(multiplier)
+        .ok_or_else(|| eyre!("Too many marbles"))?;
+    Ok(rules)
 }
 
-fn max_score(rules: Rules) -> usize {
+/// How many marbles are placed between progress reports.
+const PROGRESS_INTERVAL: usize = 1 << 16;
+
+/// Plays the marble game and returns the winning elf's score.
+///
+/// ```
+/// use aoc2018::{day09::max_score, Context};
+///
+/// let rules = "10 players; last marble is worth 1618 points".parse().unwrap();
+/// assert_eq!(8317, max_score(rules, &Context::default()));
+/// ```
+pub fn max_score(rules: Rules, ctx: &Context) -> usize {
     let mut game = VecDeque::with_capacity(rules.marbles + 1);
     let mut player = 0;
     let mut players = vec![0; rules.players];
@@ -48,6 +124,41 @@ fn max_score(rules: Rules) -> usize {
             game.push_back(marble);
         }
         debug!(?game);
+        if marble % PROGRESS_INTERVAL == 0 {
+            ctx.progress("marbles", marble as u64, Some(rules.marbles as u64));
+        }
+        player = (player + 1) % rules.players;
+    }
+    players.iter().copied().max().expect("> 0 players")
+}
+
+/// Plays the game on a circular doubly linked list stored as two arrays
+/// indexed by marble, where `next` is clockwise and `prev` anticlockwise.
+fn max_score_linked_list(rules: Rules, ctx: &Context) -> usize {
+    let mut next = vec![0; rules.marbles + 1];
+    let mut prev = vec![0; rules.marbles + 1];
+    let mut current = 0;
+    let mut player = 0;
+    let mut players = vec![0; rules.players];
+    for marble in 1..=rules.marbles {
+        if marble % 23 == 0 {
+            let removed = (0..7).fold(current, |marble, _| prev[marble]);
+            players[player] += marble + removed;
+            next[prev[removed]] = next[removed];
+            prev[next[removed]] = prev[removed];
+            current = next[removed];
+        } else {
+            let left = next[current];
+            let right = next[left];
+            next[left] = marble;
+            prev[marble] = left;
+            next[marble] = right;
+            prev[right] = marble;
+            current = marble;
+        }
+        if marble % PROGRESS_INTERVAL == 0 {
+            ctx.progress("marbles", marble as u64, Some(rules.marbles as u64));
+        }
         player = (player + 1) % rules.players;
     }
     players.iter().copied().max().expect("> 0 players")
@@ -55,51 +166,119 @@ fn max_score(rules: Rules) -> usize {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use super::*;
+    use crate::testing::{assert_variants_agree, RecordProgress, Rng};
 
     #[test]
     fn test_max_score() {
         assert_eq!(
             32,
-            max_score(Rules {
-                players: 9,
-                marbles: 25
-            }),
+            max_score(
+                Rules {
+                    players: 9,
+                    marbles: 25
+                },
+                &Context::default()
+            ),
         );
         assert_eq!(
             8317,
-            max_score(Rules {
-                players: 10,
-                marbles: 1618
-            }),
+            max_score(
+                Rules {
+                    players: 10,
+                    marbles: 1618
+                },
+                &Context::default()
+            ),
         );
         assert_eq!(
             146373,
-            max_score(Rules {
-                players: 13,
-                marbles: 7999
-            }),
+            max_score(
+                Rules {
+                    players: 13,
+                    marbles: 7999
+                },
+                &Context::default()
+            ),
         );
         assert_eq!(
             2764,
-            max_score(Rules {
-                players: 17,
-                marbles: 1104
-            }),
+            max_score(
+                Rules {
+                    players: 17,
+                    marbles: 1104
+                },
+                &Context::default()
+            ),
         );
         assert_eq!(
             54718,
-            max_score(Rules {
-                players: 21,
-                marbles: 6111
-            }),
+            max_score(
+                Rules {
+                    players: 21,
+                    marbles: 6111
+                },
+                &Context::default()
+            ),
         );
         assert_eq!(
             37305,
-            max_score(Rules {
-                players: 30,
-                marbles: 5807
-            }),
+            max_score(
+                Rules {
+                    players: 30,
+                    marbles: 5807
+                },
+                &Context::default()
+            ),
         );
     }
+
+    #[test]
+    fn reports_progress() {
+        let progress = Arc::new(RecordProgress::default());
+        let ctx = Context::default().with_progress(progress.clone());
+        max_score(
+            Rules {
+                players: 10,
+                marbles: 3 * PROGRESS_INTERVAL,
+            },
+            &ctx,
+        );
+        assert_eq!(
+            vec![
+                (
+                    "marbles",
+                    PROGRESS_INTERVAL as u64,
+                    Some(3 * PROGRESS_INTERVAL as u64)
+                ),
+                (
+                    "marbles",
+                    2 * PROGRESS_INTERVAL as u64,
+                    Some(3 * PROGRESS_INTERVAL as u64)
+                ),
+                (
+                    "marbles",
+                    3 * PROGRESS_INTERVAL as u64,
+                    Some(3 * PROGRESS_INTERVAL as u64)
+                ),
+            ],
+            progress.updates()
+        );
+    }
+
+    #[test]
+    fn variants_agree() {
+        let mut rng = Rng::new(9);
+        for _ in 0..20 {
+            let input = format!(
+                "{} players; last marble is worth {} points",
+                rng.range(1..50),
+                rng.range(1..2000),
+            );
+            assert_variants_agree(SOLUTION.part1, &input);
+            assert_variants_agree(SOLUTION.part2, &input);
+        }
+    }
 }
@@ -1,25 +1,39 @@
-use std::{collections::HashSet, str::FromStr};
+use std::str::FromStr;
 
-use eyre::{bail, eyre, Result};
+use eyre::Result;
 use nalgebra::Vector2;
 use tracing::{debug, instrument};
 
-use crate::Solution;
+#[cfg(feature = "animate")]
+use crate::animate::Frames;
+use crate::{
+    hash::HashSet,
+    parse::{self, int, literal, padded, PResult},
+    Context, Fact, Solution, Variant,
+};
 
 pub const SOLUTION: Solution = Solution {
     input: include_str!("../input/day10.txt"),
-    part1,
-    part2,
+    part1: &[Variant::new("default", part1)],
+    part2: &[Variant::new("default", part2)],
+    #[cfg(feature = "render")]
+    render: None,
+    #[cfg(feature = "animate")]
+    animate: Some(animate),
+    inspect: None,
+    params: &[],
+    examples: &[],
 };
 
+/// A point of light, parsed from `position=< 9,  1> velocity=< 0,  2>`.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
-struct Point {
-    position: Vector2<i32>,
-    velocity: Vector2<i32>,
+pub struct Point {
+    pub position: Vector2<i32>,
+    pub velocity: Vector2<i32>,
 }
 
 impl Point {
-    fn pos_at_time(&self, t: i32) -> Vector2<i32> {
+    pub fn pos_at_time(&self, t: i32) -> Vector2<i32> {
         self.position + t * self.velocity
     }
 }
@@ -27,39 +41,80 @@ impl Point {
 impl FromStr for Point {
     type Err = eyre::Report;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.strip_prefix("position=<").ok_or_else(|| eyre!(""))?;
-        let (x, s) = s.split_once(',').ok_or_else(|| eyre!(""))?;
-        let (y, s) = s.split_once('>').ok_or_else(|| eyre!(""))?;
-        let s = s.strip_prefix(" velocity=<").ok_or_else(|| eyre!(""))?;
-        let (dx, s) = s.split_once(',').ok_or_else(|| eyre!(""))?;
-        let (dy, s) = s.split_once('>').ok_or_else(|| eyre!(""))?;
-        if !s.is_empty() {
-            bail!("")
-        }
-        debug!(%s, %x, %y, %dx, %dy, "Parsed line");
-        Ok(Point {
-            position: Vector2::new(x.trim().parse()?, y.trim().parse()?),
-            velocity: Vector2::new(dx.trim().parse()?, dy.trim().parse()?),
-        })
+    fn from_str(s: &str) -> Result<Self> {
+        parse::complete(s, point)
     }
 }
 
-#[instrument(skip(input))]
-fn parse(input: &str) -> Result<Vec<Point>> {
-    input.lines().map(|line| line.trim().parse()).collect()
+fn point(input: &str) -> PResult<'_, Point> {
+    let (input, ()) = literal("position=<")(input)?;
+    let (input, position) = vector(input)?;
+    let (input, ()) = literal(" velocity=<")(input)?;
+    let (input, velocity) = vector(input)?;
+    Ok((input, Point { position, velocity }))
+}
+
+/// The rest of a vector like `< 9,  1>` after its `<`.
+fn vector(input: &str) -> PResult<'_, Vector2<i32>> {
+    let (input, x) = padded(int)(input)?;
+    let (input, ()) = literal(",")(input)?;
+    let (input, y) = padded(int)(input)?;
+    let (input, ()) = literal(">")(input)?;
+    Ok((input, Vector2::new(x, y)))
 }
 
+/// Parses one point per line, ignoring blank lines and indentation.
 #[instrument(skip(input))]
-fn part1(input: &str) -> Result<String> {
+pub fn parse(input: &str) -> Result<Vec<Point>> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| parse::line(line, i + 1, padded(point)))
+        .collect()
+}
+
+#[instrument(skip(input, ctx))]
+fn part1(input: &str, ctx: &Context) -> Result<String> {
     let points = parse(input)?;
     let time = estimate_message_time(&points);
-    let points: HashSet<_> = points.iter().map(|p| p.pos_at_time(time)).collect();
+    explain_message(&points, time, ctx);
+    Ok(render(&points, time))
+}
+
+/// Reports when the message appears and where.
+fn explain_message(points: &[Point], time: i32, ctx: &Context) {
+    ctx.explain("estimated time", time);
+    if let Some((min, max)) =
+        points
+            .iter()
+            .map(|point| point.pos_at_time(time))
+            .fold(None, |bounds, pos| match bounds {
+                None => Some((pos, pos)),
+                Some((min, max)) => Some((min.inf(&pos), max.sup(&pos))),
+            })
+    {
+        let bounding_box = [
+            ("min x", min[0]),
+            ("min y", min[1]),
+            ("max x", max[0]),
+            ("max y", max[1]),
+        ];
+        ctx.explain("bounding box", Fact::table(bounding_box));
+    }
+}
+
+/// Draws the sky at time `t` as lines of `#` and spaces, cropped to the
+/// points. The drawing starts with a newline so that it lines up when printed
+/// after a label.
+pub fn render(points: &[Point], t: i32) -> String {
+    let points: HashSet<_> = points.iter().map(|p| p.pos_at_time(t)).collect();
     let min_x = points.iter().map(|p| p[0]).min().unwrap();
     let max_x = points.iter().map(|p| p[0]).max().unwrap();
     let min_y = points.iter().map(|p| p[1]).min().unwrap();
     let max_y = points.iter().map(|p| p[1]).max().unwrap();
-    let mut answer = String::with_capacity((1 + (max_x - min_x + 2) * (max_y - min_y + 1)) as usize);
+    let mut answer =
+        String::with_capacity((1 + (max_x - min_x + 2) * (max_y - min_y + 1)) as usize);
     answer.push('\n');
     for y in min_y..=max_y {
         for x in min_x..=max_x {
@@ -71,16 +126,49 @@ fn part1(input: &str) -> Result<String> {
         }
         answer.push('\n');
     }
-    Ok(answer)
+    answer
 }
 
-#[instrument(skip(input))]
-fn part2(input: &str) -> Result<String> {
-    Ok(estimate_message_time(&parse(input)?).to_string())
+/// How many seconds either side of the estimated message time to animate.
+#[cfg(feature = "animate")]
+const ANIMATION_WINDOW: i32 = 5;
+
+/// Shows the sky each second around when the message should appear.
+#[cfg(feature = "animate")]
+fn animate(input: &str) -> Result<Frames> {
+    let points = parse(input)?;
+    let time = estimate_message_time(&points);
+    let times = (time - ANIMATION_WINDOW).max(0)..=time + ANIMATION_WINDOW;
+    Ok(Box::new(times.map(move |t| {
+        Ok(format!("t = {}{}", t, render(&points, t)))
+    })))
+}
+
+#[instrument(skip(input, ctx))]
+fn part2(input: &str, ctx: &Context) -> Result<String> {
+    let points = parse(input)?;
+    let time = estimate_message_time(&points);
+    explain_message(&points, time, ctx);
+    Ok(time.to_string())
 }
 
+/// Estimates when the points spell out a message, by finding the time at which
+/// they are closest together in the least-squares sense.
+///
+/// ```
+/// use aoc2018::day10::{estimate_message_time, parse, render};
+///
+/// let points = parse(
+///     "position=< 0, 0> velocity=< 1, 0>\n\
+///      position=< 6, 0> velocity=<-1, 0>",
+/// )
+/// .unwrap();
+/// let time = estimate_message_time(&points);
+/// assert_eq!(3, time);
+/// assert_eq!("\n#\n", render(&points, time));
+/// ```
 #[instrument]
-fn estimate_message_time(points: &[Point]) -> i32 {
+pub fn estimate_message_time(points: &[Point]) -> i32 {
     let (sum_pos, sum_vel) = points.iter().copied().fold(
         (Vector2::<f64>::default(), Vector2::<f64>::default()),
         |(pos, vel), point| (pos + point.position.cast(), vel + point.velocity.cast()),
@@ -105,6 +193,7 @@ fn estimate_message_time(points: &[Point]) -> i32 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::assert_snapshot;
 
     const TEST_INPUT: &str = "
         position=< 9,  1> velocity=< 0,  2>
@@ -143,18 +232,9 @@ mod tests {
     fn message() {
         let points = parse(TEST_INPUT).unwrap();
         assert_eq!(3, estimate_message_time(&points));
-        assert_eq!(
-            "
-      #   #  ###
-      #   #   #
-      #   #   #
-      #####   #
-      #   #   #
-      #   #   #
-      #   #   #
-      #   #  ###
-",
-            &part1(TEST_INPUT).unwrap()
+        assert_snapshot(
+            "day10_message",
+            &part1(TEST_INPUT, &Context::default()).unwrap(),
         );
     }
 }
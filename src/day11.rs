@@ -1,41 +1,69 @@
-use bytemuck::zeroed_box;
 use eyre::Result;
 use tracing::instrument;
 
-use crate::Solution;
+#[cfg(feature = "render")]
+use crate::render::{palette, Image};
+use crate::{
+    grid::{Grid, Point},
+    Context, Solution, Variant,
+};
 
 pub const SOLUTION: Solution = Solution {
     input: include_str!("../input/day11.txt"),
-    part1,
-    part2,
+    part1: &[Variant::new("default", part1)],
+    part2: &[
+        Variant::new("doubling", part2),
+        Variant::new("summed-area", part2_summed_area),
+    ],
+    #[cfg(feature = "render")]
+    render: Some(render),
+    #[cfg(feature = "animate")]
+    animate: None,
+    inspect: None,
+    params: &[],
+    examples: &[],
 };
 
-const GRID_SIZE: usize = 300;
+/// The width and height of the fuel cell grid.
+pub const GRID_SIZE: usize = 300;
 
-fn make_grid(serial: i32) -> [[i32; GRID_SIZE]; GRID_SIZE] {
-    let mut grid = [[0; GRID_SIZE]; GRID_SIZE];
-    #[allow(clippy::needless_range_loop)]
-    for y in 0..GRID_SIZE {
-        for x in 0..GRID_SIZE {
-            let rack_id = x as i32 + 11;
-            let mut power_level = rack_id * (y as i32 + 1);
-            power_level += serial;
-            power_level *= rack_id;
-            let hundreds_digit = (power_level / 100) % 10;
-            grid[y][x] = hundreds_digit - 5;
-        }
-    }
-    grid
+/// The power level of every fuel cell for a grid serial number, indexed by
+/// `Point::new(x - 1, y - 1)` since the puzzle's coordinates start at 1.
+///
+/// ```
+/// use aoc2018::{day11::make_grid, grid::Point};
+///
+/// let grid = make_grid(57);
+/// assert_eq!(-5, grid[Point::new(122 - 1, 79 - 1)]);
+/// ```
+pub fn make_grid(serial: i32) -> Grid<i32> {
+    Grid::from_fn(GRID_SIZE, GRID_SIZE, |point| {
+        let rack_id = point.x as i32 + 11;
+        let mut power_level = rack_id * (point.y as i32 + 1);
+        power_level += serial;
+        power_level *= rack_id;
+        let hundreds_digit = (power_level / 100) % 10;
+        hundreds_digit - 5
+    })
+}
+
+/// Draws the power level of every fuel cell, from -5 in blue up to 4 in
+/// yellow.
+#[cfg(feature = "render")]
+fn render(input: &str, _ctx: &Context) -> Result<Image> {
+    let grid = make_grid(input.trim().parse()?);
+    Ok(grid.map(|_, &power| palette::heat((power + 5) as f64 / 9.0)))
 }
 
-#[instrument(skip(input))]
-fn part1(input: &str) -> Result<String> {
+#[instrument(skip(input, _ctx))]
+fn part1(input: &str, _ctx: &Context) -> Result<String> {
     let grid = make_grid(input.trim().parse()?);
     let (x, y) = (1..=GRID_SIZE - 2)
         .flat_map(|y| (1..=GRID_SIZE - 2).map(move |x| (x, y)))
         .max_by_key(|&(x, y)| {
-            grid[y - 1..y + 2]
-                .iter()
+            grid.rows()
+                .skip(y - 1)
+                .take(3)
                 .flat_map(|row| row[x - 1..x + 2].iter().copied())
                 .sum::<i32>()
         })
@@ -43,28 +71,31 @@ fn part1(input: &str) -> Result<String> {
     Ok(format!("{x},{y}"))
 }
 
-#[instrument(skip(input))]
-fn part2(input: &str) -> Result<String> {
-    let mut grids: Box<[[[i32; GRID_SIZE]; GRID_SIZE]; GRID_SIZE + 1]> = zeroed_box();
+#[instrument(skip(input, ctx))]
+fn part2(input: &str, ctx: &Context) -> Result<String> {
+    let mut grids = vec![Grid::new(GRID_SIZE, GRID_SIZE, 0); GRID_SIZE + 1];
     grids[1] = make_grid(input.trim().parse()?);
+    let at = |x, y| Point::new(x, y);
     for size in 2..=GRID_SIZE {
+        ctx.progress("grid size", size as u64, Some(GRID_SIZE as u64));
         for y in 0..=GRID_SIZE - size {
             for x in 0..=GRID_SIZE - size {
-                grids[size][y][x] = if size % 2 == 0 {
-                    let half_grid = &grids[size / 2];
-                    half_grid[y][x]
-                        + half_grid[y][x + size / 2]
-                        + half_grid[y + size / 2][x]
-                        + half_grid[y + size / 2][x + size / 2]
+                grids[size][at(x, y)] = if size % 2 == 0 {
+                    let half = size / 2;
+                    let half_grid = &grids[half];
+                    half_grid[at(x, y)]
+                        + half_grid[at(x + half, y)]
+                        + half_grid[at(x, y + half)]
+                        + half_grid[at(x + half, y + half)]
                 } else {
                     let mid = size / 2;
                     let half_grid_up = &grids[mid + 1];
                     let half_grid_down = &grids[mid];
-                    half_grid_up[y][x]
-                        + half_grid_up[y + mid][x + mid]
-                        + half_grid_down[y + mid + 1][x]
-                        + half_grid_down[y][x + mid + 1]
-                        - grids[1][y + mid][x + mid]
+                    half_grid_up[at(x, y)]
+                        + half_grid_up[at(x + mid, y + mid)]
+                        + half_grid_down[at(x, y + mid + 1)]
+                        + half_grid_down[at(x + mid + 1, y)]
+                        - grids[1][at(x + mid, y + mid)]
                 };
             }
         }
@@ -72,7 +103,44 @@ fn part2(input: &str) -> Result<String> {
     let (x, y, size) = (1..=GRID_SIZE)
         .flat_map(|size| (1..=GRID_SIZE - size + 1).map(move |y| (y, size)))
         .flat_map(|(y, size)| (1..=GRID_SIZE - size + 1).map(move |x| (x, y, size)))
-        .max_by_key(|&(x, y, size)| grids[size][y - 1][x - 1])
+        .max_by_key(|&(x, y, size)| grids[size][at(x - 1, y - 1)])
         .unwrap();
     Ok(format!("{x},{y},{size}"))
 }
+
+/// Finds the best square using a summed-area table, where `sums[(x, y)]` is
+/// the total power of every cell above and to the left of `(x, y)`.
+#[instrument(skip(input, _ctx))]
+fn part2_summed_area(input: &str, _ctx: &Context) -> Result<String> {
+    let grid = make_grid(input.trim().parse()?);
+    let at = |x, y| Point::new(x, y);
+    let mut sums = Grid::new(GRID_SIZE + 1, GRID_SIZE + 1, 0);
+    for (point, &power) in grid.iter() {
+        let (x, y) = (point.x, point.y);
+        sums[at(x + 1, y + 1)] = power + sums[at(x + 1, y)] + sums[at(x, y + 1)] - sums[at(x, y)];
+    }
+    let (x, y, size) = (1..=GRID_SIZE)
+        .flat_map(|size| (1..=GRID_SIZE - size + 1).map(move |y| (y, size)))
+        .flat_map(|(y, size)| (1..=GRID_SIZE - size + 1).map(move |x| (x, y, size)))
+        .max_by_key(|&(x, y, size)| {
+            let (x, y) = (x - 1, y - 1);
+            sums[at(x + size, y + size)] - sums[at(x + size, y)] - sums[at(x, y + size)]
+                + sums[at(x, y)]
+        })
+        .unwrap();
+    Ok(format!("{x},{y},{size}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{assert_variants_agree, Rng};
+
+    #[test]
+    fn variants_agree() {
+        let mut rng = Rng::new(11);
+        for _ in 0..3 {
+            assert_variants_agree(SOLUTION.part2, &rng.range(1..10_000).to_string());
+        }
+    }
+}
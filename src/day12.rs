@@ -1,28 +1,100 @@
-use std::fmt::{self, Write};
+use std::{
+    fmt::{self, Write},
+    iter,
+};
 
 use bitvec::prelude::*;
 use eyre::{eyre, Result};
 use tracing::instrument;
 
-use crate::Solution;
+use crate::{cycle, Context, Example, Param, Solution, Variant};
+#[cfg(feature = "render")]
+use crate::{
+    grid::{Grid, Point},
+    render::{palette, Image},
+};
 
 pub const SOLUTION: Solution = Solution {
     input: include_str!("../input/day12.txt"),
-    part1,
-    part2,
+    part1: &[Variant::new("default", part1)],
+    part2: &[Variant::new("default", part2)],
+    #[cfg(feature = "render")]
+    render: Some(render),
+    #[cfg(feature = "animate")]
+    animate: None,
+    inspect: None,
+    params: &[GENERATIONS],
+    examples: &[Example {
+        input: EXAMPLE,
+        params: &[("generations", 20)],
+        answers: [Some("325"), Some("325")],
+    }],
 };
 
-struct Pots {
+/// The example from the puzzle statement.
+const EXAMPLE: &str = "\
+    initial state: #..#.#..##......###...###\n\
+    \n\
+    ..... => .\n\
+    ....# => .\n\
+    ...#. => .\n\
+    ...## => #\n\
+    ..#.. => #\n\
+    ..#.# => .\n\
+    ..##. => .\n\
+    ..### => .\n\
+    .#... => #\n\
+    .#..# => .\n\
+    .#.#. => #\n\
+    .#.## => #\n\
+    .##.. => #\n\
+    .##.# => .\n\
+    .###. => .\n\
+    .#### => #\n\
+    #.... => .\n\
+    #...# => .\n\
+    #..#. => .\n\
+    #..## => .\n\
+    #.#.. => .\n\
+    #.#.# => #\n\
+    #.##. => .\n\
+    #.### => #\n\
+    ##... => .\n\
+    ##..# => .\n\
+    ##.#. => #\n\
+    ##.## => #\n\
+    ###.. => #\n\
+    ###.# => #\n\
+    ####. => #\n\
+    ##### => .\n\
+";
+
+const GENERATIONS: Param = Param::new(
+    "generations",
+    50_000_000_000,
+    "How many generations part 2 looks ahead",
+)
+.at_least(0);
+
+/// A row of pots, only some of which contain plants.
+///
+/// Only a window around the plants is stored, `zero` is the index of pot 0
+/// within that window and may lie outside it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pots {
     pots: BitVec,
     zero: isize,
 }
 
 impl Pots {
-    fn score(&self) -> u64 {
-        self.pots
-            .iter_ones()
-            .map(|i| i as isize - self.zero as isize)
-            .sum::<isize>() as u64
+    /// The numbers of the pots containing plants, in increasing order.
+    pub fn plants(&self) -> impl Iterator<Item = isize> + '_ {
+        self.pots.iter_ones().map(|i| i as isize - self.zero)
+    }
+
+    /// The sum of the numbers of the pots containing plants.
+    pub fn score(&self) -> u64 {
+        self.plants().sum::<isize>() as u64
     }
 }
 
@@ -43,14 +115,28 @@ impl fmt::Display for Pots {
     }
 }
 
-struct Plants {
+/// The pots and the rules that decide how they spread.
+#[derive(Debug, Clone)]
+pub struct Plants {
     rules: u32,
     current: Pots,
     previous: Pots,
 }
 
 impl Plants {
-    fn step(&mut self) -> Result<bool> {
+    /// The pots in the current generation.
+    pub fn current(&self) -> &Pots {
+        &self.current
+    }
+
+    /// The pots in the previous generation.
+    pub fn previous(&self) -> &Pots {
+        &self.previous
+    }
+
+    /// Advances one generation, returning whether the pattern of plants
+    /// changed, ignoring any shift along the row. Fails if every plant dies.
+    pub fn step(&mut self) -> Result<bool> {
         std::mem::swap(&mut self.current, &mut self.previous);
         self.current.pots.clear();
 
@@ -80,7 +166,26 @@ fn plant_state(c: char) -> Result<bool> {
     }
 }
 
-fn parse(input: &str) -> Result<Plants> {
+/// Parses the initial state and exactly one rule for each of the 32 patterns.
+///
+/// ```
+/// use std::fmt::Write;
+///
+/// let mut input = String::from("initial state: #..#\n\n");
+/// for pattern in 0..32 {
+///     for bit in 0..5 {
+///         input.push(if pattern & (1 << bit) != 0 { '#' } else { '.' });
+///     }
+///     // Plants only survive in pots with a plant immediately to their left.
+///     let survives = pattern & 0b00010 != 0;
+///     writeln!(input, " => {}", if survives { '#' } else { '.' }).unwrap();
+/// }
+/// let mut plants = aoc2018::day12::parse(&input).unwrap();
+/// assert_eq!(3, plants.current().score());
+/// plants.step().unwrap();
+/// assert_eq!(5, plants.current().score());
+/// ```
+pub fn parse(input: &str) -> Result<Plants> {
     let mut lines = input.lines();
     let mut initial_state = BitVec::new();
     initial_state.extend_from_bitslice(bits![0; 4]);
@@ -98,7 +203,7 @@ fn parse(input: &str) -> Result<Plants> {
     initial_state.extend_from_bitslice(bits![0; 4]);
 
     let spacing_line = lines.next().ok_or_else(|| eyre!("Missing rules"))?;
-    if !spacing_line.is_empty() {
+    if !spacing_line.trim().is_empty() {
         return Err(eyre!("Spacing line not empty: {spacing_line:?}"));
     }
     let mut mask: u32 = 0;
@@ -147,27 +252,102 @@ fn parse(input: &str) -> Result<Plants> {
     })
 }
 
-#[instrument(skip(input))]
-fn part1(input: &str) -> Result<String> {
+/// Draws the first 20 generations one row each, with plants in green.
+#[cfg(feature = "render")]
+fn render(input: &str, _ctx: &Context) -> Result<Image> {
     let mut state = parse(input)?;
+    let mut generations = vec![state.current().plants().collect::<Vec<_>>()];
     for _ in 0..20 {
         state.step()?;
+        generations.push(state.current().plants().collect());
     }
-    Ok(state.current.score().to_string())
+    let plants = generations.iter().flatten();
+    let first = plants.clone().copied().min().unwrap_or(0);
+    let last = plants.copied().max().unwrap_or(0);
+    let mut image = Grid::new(
+        (last - first + 1) as usize,
+        generations.len(),
+        palette::FLOOR,
+    );
+    for (generation, plants) in generations.iter().enumerate() {
+        for plant in plants {
+            let point = Point::new((plant - first) as usize, generation);
+            image[point] = palette::ELF;
+        }
+    }
+    Ok(image)
 }
 
-#[instrument(skip(input))]
-fn part2(input: &str) -> Result<String> {
+#[instrument(skip(input, ctx))]
+fn part1(input: &str, ctx: &Context) -> Result<String> {
     let mut state = parse(input)?;
-    let mut generation = 0u64;
-    let generation = loop {
-        generation += 1;
-        if !state.step()? {
-            break generation;
+    for _ in 0..20 {
+        state.step()?;
+    }
+    ctx.count("generations", 20);
+    Ok(state.current.score().to_string())
+}
+
+/// The score after [`GENERATIONS`] generations.
+///
+/// The pattern eventually repeats, typically shifted along the row, after
+/// which the score changes by the same amount each period.
+#[instrument(skip(input, ctx))]
+fn part2(input: &str, ctx: &Context) -> Result<String> {
+    let generations: u64 = ctx.param(&GENERATIONS)?;
+    let mut plants = parse(input)?;
+    let mut scores = Vec::new();
+    let mut failure = Ok(());
+    let patterns = iter::from_fn(|| {
+        if !scores.is_empty() {
+            let generation = scores.len();
+            let step = ctx
+                .checkpoint(|| format!("generation {}", generation))
+                .and_then(|_| plants.step());
+            if let Err(error) = step {
+                failure = Err(error);
+                return None;
+            }
+            ctx.progress("generation", generation as u64, None);
         }
+        scores.push(plants.current().score() as i64);
+        Some(plants.current().pots.clone())
+    })
+    .take(usize::try_from(generations).map_or(usize::MAX, |generations| generations + 1));
+    let found = cycle::find(patterns);
+    failure?;
+    ctx.count("generations", scores.len() as u64 - 1);
+    let cycle = match found {
+        Some((cycle, _)) => cycle,
+        None => return Ok(scores.last().unwrap().to_string()),
     };
-    let old_score = state.previous.score();
-    let new_score = state.current.score();
+    ctx.explain("stable from generation", cycle.start);
+    ctx.explain("period", cycle.period);
+    ctx.explain(
+        "delta per period",
+        scores[cycle.start + cycle.period] - scores[cycle.start],
+    );
+    Ok(cycle
+        .extrapolate(generations, |generation| scores[generation])
+        .to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::assert_snapshot;
 
-    Ok((new_score + (new_score - old_score) * (50_000_000_000 - generation)).to_string())
+    /// The first 20 generations of the example, as listed in the puzzle.
+    #[test]
+    fn generations() {
+        let mut plants = parse(EXAMPLE).unwrap();
+        let mut snapshot = String::new();
+        for generation in 0..=20 {
+            if generation > 0 {
+                plants.step().unwrap();
+            }
+            write!(snapshot, "generation {}:\n{}", generation, plants.current()).unwrap();
+        }
+        assert_snapshot("day12_generations", &snapshot);
+    }
 }
@@ -1,69 +1,38 @@
-use core::fmt;
+#[cfg(feature = "animate")]
+use std::iter;
 use std::{
     collections::{btree_map::Entry, BTreeMap},
-    ops::Add,
+    fmt,
     str::FromStr,
 };
 
 use eyre::{bail, eyre, Result};
 use tracing::instrument;
 
-use crate::Solution;
+#[cfg(feature = "animate")]
+use crate::animate::Frames;
+use crate::{
+    grid::{Direction, Grid, Point},
+    Context, Solution, Variant,
+};
 
 pub const SOLUTION: Solution = Solution {
     input: include_str!("../input/day13.txt"),
-    part1,
-    part2,
+    part1: &[Variant::new("default", part1)],
+    part2: &[Variant::new("default", part2)],
+    #[cfg(feature = "render")]
+    render: None,
+    #[cfg(feature = "animate")]
+    animate: Some(animate),
+    inspect: None,
+    params: &[],
+    examples: &[],
 };
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Direction {
-    North,
-    South,
-    East,
-    West,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct Location {
-    row: usize,
-    column: usize,
-}
-
-impl fmt::Display for Location {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{},{}", self.column, self.row)
-    }
-}
-
-impl Add<Direction> for Location {
-    type Output = Location;
-
-    fn add(self, rhs: Direction) -> Self::Output {
-        match rhs {
-            Direction::North => Location {
-                row: self.row - 1,
-                column: self.column,
-            },
-            Direction::South => Location {
-                row: self.row + 1,
-                column: self.column,
-            },
-            Direction::East => Location {
-                row: self.row,
-                column: self.column + 1,
-            },
-            Direction::West => Location {
-                row: self.row,
-                column: self.column - 1,
-            },
-        }
-    }
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Track {
-    Straight,
+    Vertical,
+    Horizontal,
     /// Turns like ╯╭
     TurnA,
     /// Turns like ╮╰
@@ -96,7 +65,7 @@ impl CartState {
         use Direction::*;
         let mut next_turn = self.next_turn;
         let direction = match (track, self.direction) {
-            (Track::Straight, dir) => dir,
+            (Track::Vertical | Track::Horizontal, dir) => dir,
             (Track::TurnA, North) => East,
             (Track::TurnA, South) => West,
             (Track::TurnA, East) => North,
@@ -106,16 +75,10 @@ impl CartState {
             (Track::TurnB, East) => South,
             (Track::TurnB, West) => North,
             (Track::TurnAny, dir) => {
-                let direction = match (next_turn, dir) {
-                    (NextTurn::Left, North) => West,
-                    (NextTurn::Left, West) => South,
-                    (NextTurn::Left, South) => East,
-                    (NextTurn::Left, East) => North,
-                    (NextTurn::Forward, dir) => dir,
-                    (NextTurn::Right, North) => East,
-                    (NextTurn::Right, East) => South,
-                    (NextTurn::Right, South) => West,
-                    (NextTurn::Right, West) => North,
+                let direction = match next_turn {
+                    NextTurn::Left => dir.turn_left(),
+                    NextTurn::Forward => dir,
+                    NextTurn::Right => dir.turn_right(),
                 };
                 next_turn = match next_turn {
                     NextTurn::Left => NextTurn::Forward,
@@ -132,81 +95,72 @@ impl CartState {
     }
 }
 
-#[derive(Debug)]
-struct State {
-    map: Vec<Vec<Option<Track>>>,
-    carts: BTreeMap<Location, CartState>,
+/// The track layout and every cart still on it.
+#[derive(Debug, Clone)]
+pub struct State {
+    map: Grid<Option<Track>>,
+    carts: BTreeMap<Point, CartState>,
 }
 
 impl FromStr for State {
-    type Err = color_eyre::Report;
+    type Err = eyre::Report;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut carts = BTreeMap::new();
-        let map = s
-            .lines()
-            .enumerate()
-            .map(|(row, line)| {
-                line.as_bytes()
-                    .iter()
-                    .enumerate()
-                    .map(|(column, &symbol)| {
-                        Ok(Some(match symbol {
-                            b' ' => return Ok(None),
-                            b'|' | b'-' => Track::Straight,
-                            b'/' => Track::TurnA,
-                            b'\\' => Track::TurnB,
-                            b'+' => Track::TurnAny,
-                            b'^' => {
-                                carts.insert(
-                                    Location { row, column },
-                                    CartState::new(Direction::North),
-                                );
-                                Track::Straight
-                            }
-                            b'v' => {
-                                carts.insert(
-                                    Location { row, column },
-                                    CartState::new(Direction::South),
-                                );
-                                Track::Straight
-                            }
-                            b'>' => {
-                                carts.insert(
-                                    Location { row, column },
-                                    CartState::new(Direction::East),
-                                );
-                                Track::Straight
-                            }
-                            b'<' => {
-                                carts.insert(
-                                    Location { row, column },
-                                    CartState::new(Direction::West),
-                                );
-                                Track::Straight
-                            }
-                            _ => bail!("Unexpected symbol in map: {:?}", symbol),
-                        }))
-                    })
-                    .collect()
-            })
-            .collect::<Result<_>>()?;
+        let map = Grid::parse_ragged(s, None, |point, symbol| {
+            let direction = match symbol {
+                ' ' => return Ok(None),
+                '|' => return Ok(Some(Track::Vertical)),
+                '-' => return Ok(Some(Track::Horizontal)),
+                '/' => return Ok(Some(Track::TurnA)),
+                '\\' => return Ok(Some(Track::TurnB)),
+                '+' => return Ok(Some(Track::TurnAny)),
+                '^' => Direction::North,
+                'v' => Direction::South,
+                '>' => Direction::East,
+                '<' => Direction::West,
+                _ => bail!("Unexpected symbol in map: {:?}", symbol),
+            };
+            carts.insert(point, CartState::new(direction));
+            Ok(Some(match direction {
+                Direction::North | Direction::South => Track::Vertical,
+                Direction::East | Direction::West => Track::Horizontal,
+            }))
+        })?;
         Ok(Self { map, carts })
     }
 }
 
 impl State {
-    fn step(&mut self) -> Result<Vec<Location>> {
+    /// The locations of the remaining carts, in the order they will move.
+    pub fn carts(&self) -> impl Iterator<Item = Point> + '_ {
+        self.carts.keys().copied()
+    }
+
+    /// Moves every cart one step and returns where carts crashed. Crashed
+    /// carts are removed immediately.
+    ///
+    /// ```
+    /// use aoc2018::{day13::State, grid::Point};
+    ///
+    /// let mut state: State = "->--<-".parse().unwrap();
+    /// assert!(state.step().unwrap().is_empty());
+    /// assert_eq!(vec![Point::new(3, 0)], state.step().unwrap());
+    /// assert_eq!(0, state.carts().count());
+    /// ```
+    pub fn step(&mut self) -> Result<Vec<Point>> {
         let mut crashes = Vec::new();
         for old_loc in self.carts.keys().copied().collect::<Vec<_>>() {
             let cart = match self.carts.remove(&old_loc) {
                 Some(cart) => cart,
                 None => continue,
             };
-            let track = self.map[old_loc.row][old_loc.column]
-                .ok_or_else(|| eyre!("Fell off track at {}", old_loc))?;
+            let track = self.map[old_loc].ok_or_else(|| eyre!("Fell off track at {}", old_loc))?;
             let new_cart = cart.step(track);
-            let new_loc = old_loc + new_cart.direction;
+            let new_loc = self
+                .map
+                .neighbour(old_loc, new_cart.direction)
+                .ok_or_else(|| eyre!("Drove off the map at {}", old_loc))?;
             match self.carts.entry(new_loc) {
                 Entry::Vacant(entry) => {
                     entry.insert(new_cart);
@@ -221,24 +175,87 @@ impl State {
     }
 }
 
-#[instrument(skip(input))]
-fn part1(input: &str) -> Result<String> {
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let drawing = self.map.display_with(|point, track| {
+            if let Some(cart) = self.carts.get(&point) {
+                return match cart.direction {
+                    Direction::North => '^',
+                    Direction::East => '>',
+                    Direction::South => 'v',
+                    Direction::West => '<',
+                };
+            }
+            match track {
+                None => ' ',
+                Some(Track::Vertical) => '|',
+                Some(Track::Horizontal) => '-',
+                Some(Track::TurnA) => '/',
+                Some(Track::TurnB) => '\\',
+                Some(Track::TurnAny) => '+',
+            }
+        });
+        fmt::Display::fmt(&drawing, f)
+    }
+}
+
+/// Shows the carts every tick until at most one is left.
+#[cfg(feature = "animate")]
+fn animate(input: &str) -> Result<Frames> {
+    let mut state: State = input.parse()?;
+    let mut tick = 0;
+    let mut crashes = Vec::new();
+    let mut finished = false;
+    Ok(Box::new(iter::from_fn(move || {
+        if finished {
+            return None;
+        }
+        let mut frame = format!("tick {}, {} carts", tick, state.carts.len());
+        for crash in &crashes {
+            frame += &format!(", crash at {}", crash);
+        }
+        frame += &format!("\n{}", state);
+        finished = state.carts.len() <= 1;
+        if !finished {
+            match state.step() {
+                Ok(new_crashes) => crashes = new_crashes,
+                Err(error) => {
+                    finished = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+        tick += 1;
+        Some(Ok(frame))
+    })))
+}
+
+#[instrument(skip(input, ctx))]
+fn part1(input: &str, ctx: &Context) -> Result<String> {
     let mut state: State = input.parse()?;
+    let mut ticks = 0;
     let collision = loop {
+        ctx.check()?;
         let crashes = state.step()?;
+        ticks += 1;
         if let Some(&collision) = crashes.first() {
             break collision;
         }
     };
+    ctx.count("ticks", ticks);
     Ok(collision.to_string())
 }
 
-#[instrument(skip(input))]
-fn part2(input: &str) -> Result<String> {
+#[instrument(skip(input, ctx))]
+fn part2(input: &str, ctx: &Context) -> Result<String> {
     let mut state: State = input.parse()?;
+    let mut ticks = 0;
     while state.carts.len() > 1 {
+        ctx.check()?;
         state.step()?;
+        ticks += 1;
     }
+    ctx.count("ticks", ticks);
     let final_loc = state.carts.into_keys().next().unwrap();
     Ok(final_loc.to_string())
 }
@@ -246,6 +263,8 @@ fn part2(input: &str) -> Result<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "animate")]
+    use crate::testing::assert_snapshot;
 
     const EXAMPLE: &str = r#"
         /->-\
@@ -258,6 +277,34 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!("15,4", part1(EXAMPLE).unwrap());
+        assert_eq!("15,4", part1(EXAMPLE, &Context::default()).unwrap());
+    }
+
+    #[cfg(feature = "animate")]
+    const CRASHES: &str = "\
+        />-<\\  \n\
+        |   |  \n\
+        | /<+-\\\n\
+        | | | v\n\
+        \\>+</ |\n\
+        \x20 |   ^\n\
+        \x20 \\<->/\n\
+    ";
+
+    #[cfg(feature = "animate")]
+    #[test]
+    fn animation_ends_with_one_cart() {
+        let frames: Vec<_> = animate(CRASHES).unwrap().collect::<Result<_>>().unwrap();
+        assert_eq!(CRASHES, frames[0].split_once('\n').unwrap().1);
+        let last = frames.last().unwrap();
+        assert!(last.starts_with("tick 3, 1 carts"), "{}", last);
+        assert_eq!("6,4", part2(CRASHES, &Context::default()).unwrap());
+    }
+
+    #[cfg(feature = "animate")]
+    #[test]
+    fn frames() {
+        let frames: Vec<_> = animate(CRASHES).unwrap().collect::<Result<_>>().unwrap();
+        assert_snapshot("day13_frames", &frames.join("\n"));
     }
 }
@@ -1,17 +1,32 @@
 use eyre::Result;
 use tracing::instrument;
 
-use crate::Solution;
+use crate::{Context, Solution, Variant};
 
 pub const SOLUTION: Solution = Solution {
     input: include_str!("../input/day14.txt"),
-    part1,
-    part2,
+    part1: &[Variant::new("default", part1)],
+    part2: &[Variant::new("default", part2)],
+    #[cfg(feature = "render")]
+    render: None,
+    #[cfg(feature = "animate")]
+    animate: None,
+    inspect: None,
+    params: &[],
+    examples: &[],
 };
 
-#[instrument(skip(input))]
-fn part1(input: &str) -> Result<String> {
-    let input: usize = input.trim().parse()?;
+#[instrument(skip(input, _ctx))]
+fn part1(input: &str, _ctx: &Context) -> Result<String> {
+    Ok(scores_after(input.trim().parse()?))
+}
+
+/// The scores of the ten recipes made after the first `recipes`.
+///
+/// ```
+/// assert_eq!("5158916779", aoc2018::day14::scores_after(9));
+/// ```
+pub fn scores_after(input: usize) -> String {
     let mut recipes = vec![3, 7];
     let mut elves = [0, 1];
     while recipes.len() < input + 10 {
@@ -26,23 +41,39 @@ fn part1(input: &str) -> Result<String> {
             *elf = (*elf + 1 + recipes[*elf]) % recipes.len();
         }
     }
-    let recipes: String = recipes[input..][..10]
+    recipes[input..][..10]
         .iter()
         .map(|&recipe| char::from_digit(recipe as u32, 10).unwrap())
-        .collect();
-    Ok(recipes)
+        .collect()
 }
 
-#[instrument(skip(input))]
-fn part2(input: &str) -> Result<String> {
+/// Roughly how many recipes are created between progress reports.
+const PROGRESS_INTERVAL: usize = 1 << 16;
+
+#[instrument(skip(input, ctx))]
+fn part2(input: &str, ctx: &Context) -> Result<String> {
     let input: Vec<_> = input
         .trim()
         .chars()
         .map(|c| c.to_digit(10).unwrap() as usize)
         .collect();
+    recipes_before(&input, ctx).map(|start| start.to_string())
+}
+
+/// How many recipes are made before the scores in `input` first appear.
+///
+/// This gives up once `ctx` is cancelled, since some sequences never appear.
+///
+/// ```
+/// use aoc2018::{day14::recipes_before, Context};
+///
+/// assert_eq!(9, recipes_before(&[5, 1, 5, 8, 9], &Context::default()).unwrap());
+/// ```
+pub fn recipes_before(input: &[usize], ctx: &Context) -> Result<usize> {
     let mut recipes = vec![3, 7];
     let mut elves = [0, 1];
     let start = 'outer: loop {
+        ctx.checkpoint(|| format!("{} recipes", recipes.len()))?;
         let new_recipes: usize = elves.iter().map(|&elf| recipes[elf]).sum();
         let new_recipes: Vec<_> = new_recipes
             .to_string()
@@ -50,6 +81,9 @@ fn part2(input: &str) -> Result<String> {
             .map(|c: char| c.to_digit(10).unwrap() as usize)
             .collect();
         recipes.extend(new_recipes.iter().copied());
+        if recipes.len() % PROGRESS_INTERVAL < new_recipes.len() {
+            ctx.progress("recipes", recipes.len() as u64, None);
+        }
         for elf in &mut elves {
             *elf = (*elf + 1 + recipes[*elf]) % recipes.len();
         }
@@ -61,20 +95,36 @@ fn part2(input: &str) -> Result<String> {
             }
         }
     };
-    Ok(start.to_string())
+    ctx.count("recipes", recipes.len() as u64);
+    Ok(start)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{CancelReason, CancelToken, Cancelled};
 
     #[test]
     fn test_part1() {
-        assert_eq!("5941429882", part1("2018").unwrap());
+        assert_eq!("5941429882", part1("2018", &Context::default()).unwrap());
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!("2018", part2("59414").unwrap());
+        assert_eq!("2018", part2("59414", &Context::default()).unwrap());
+    }
+
+    #[test]
+    fn interrupted() {
+        let cancel = CancelToken::new();
+        cancel.cancel(CancelReason::Interrupted);
+        let error = part2("59414", &Context::new(cancel)).unwrap_err();
+        assert_eq!(
+            Some(&Cancelled {
+                reason: CancelReason::Interrupted
+            }),
+            error.downcast_ref::<Cancelled>()
+        );
+        assert_eq!("2 recipes: interrupted", format!("{:#}", error));
     }
 }
@@ -1,56 +1,71 @@
 use std::{
-    cmp::Reverse,
-    collections::{BTreeMap, BinaryHeap, HashSet},
+    collections::BTreeMap,
     fmt::{self, Display, Write},
+    mem,
 };
 
-use bitvec::vec::BitVec;
 use eyre::{bail, eyre, Result};
 use tracing::instrument;
 
-use crate::Solution;
+#[cfg(feature = "animate")]
+use crate::animate::Frames;
+#[cfg(feature = "render")]
+use crate::render::{palette, Image};
+use crate::{
+    grid::{Grid, Point},
+    hash::HashSet,
+    search::Search,
+    Context, Example, Fact, Param, Solution, Variant,
+};
 
 pub const SOLUTION: Solution = Solution {
     input: include_str!("../input/day15.txt"),
-    part1,
-    part2,
+    part1: &[Variant::new("default", part1)],
+    part2: &[Variant::new("default", part2)],
+    #[cfg(feature = "render")]
+    render: Some(render),
+    #[cfg(feature = "animate")]
+    animate: Some(animate),
+    inspect: Some(inspect),
+    params: &[MIN_ATTACK],
+    examples: &[Example {
+        input: EXAMPLE,
+        params: &[],
+        answers: [Some("27730"), Some("4988")],
+    }],
 };
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Location {
-    x: usize,
-    y: usize,
-}
-
-impl PartialOrd for Location {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Ord for Location {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        usize::cmp(&self.y, &other.y).then_with(|| usize::cmp(&self.x, &other.x))
-    }
-}
-
-impl fmt::Display for Location {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "({}, {})", self.x, self.y)
-    }
-}
+/// The example from the puzzle statement.
+const EXAMPLE: &str = "\
+    #######\n\
+    #.G...#\n\
+    #...EG#\n\
+    #.#.#G#\n\
+    #..G#E#\n\
+    #.....#\n\
+    #######\n\
+";
+
+const MIN_ATTACK: Param = Param::new(
+    "min_attack",
+    4,
+    "The lowest elf attack power part 2 tries, goblins always have 3",
+)
+.at_least(1)
+.at_most(u8::MAX as i64);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Allegiance {
+pub enum Allegiance {
     Elf,
     Goblin,
 }
 
-#[derive(Debug)]
-struct Unit {
-    allegiance: Allegiance,
-    health: u8,
-    attack: u8,
+/// An elf or goblin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unit {
+    pub allegiance: Allegiance,
+    pub health: u8,
+    pub attack: u8,
 }
 
 impl fmt::Display for Unit {
@@ -91,52 +106,69 @@ impl fmt::Display for Tile<'_> {
     }
 }
 
-#[derive(Debug)]
-struct Map {
+/// The cave and the units fighting in it.
+#[derive(Debug, Clone)]
+pub struct Map {
     round: u32,
-    width: usize,
-    height: usize,
-    grid: BitVec,
-    units: BTreeMap<Location, Unit>,
+    walls: Grid<bool>,
+    units: BTreeMap<Point, Unit>,
+    /// Reused by every unit's moves for the whole battle.
+    search: Search<Point>,
 }
 
 impl Map {
-    fn builder() -> MapBuilder {
+    pub fn builder() -> MapBuilder {
         MapBuilder::default()
     }
 
-    fn get(&self, location: Location) -> Tile<'_> {
-        if location.x >= self.width || location.y >= self.height {
-            panic!(
-                "{} is out of bounds of map of size ({}, {})",
-                location, self.width, self.height
-            );
-        }
-        if self.grid[location.y * self.width + location.x] {
+    /// The number of full rounds completed so far.
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    pub fn width(&self) -> usize {
+        self.walls.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.walls.height()
+    }
+
+    /// Whether `location` is a wall. Panics if it is outside the map.
+    pub fn is_wall(&self, location: Point) -> bool {
+        matches!(self.get(location), Tile::Wall)
+    }
+
+    /// The living units, in the order they take their turns.
+    pub fn units(&self) -> impl Iterator<Item = (Point, &Unit)> {
+        self.units.iter().map(|(&location, unit)| (location, unit))
+    }
+
+    fn get(&self, location: Point) -> Tile<'_> {
+        if self.walls[location] {
             Tile::Wall
         } else {
             Tile::Floor(self.units.get(&location))
         }
     }
 
-    fn adjacent_locations(&self, Location { x, y }: Location) -> impl Iterator<Item = Location> {
-        [
-            y.checked_sub(1).map(|y| Location { x, y }),
-            x.checked_sub(1).map(|x| Location { x, y }),
-            x.checked_add(1)
-                .filter(|&x| x < self.width)
-                .map(|x| Location { x, y }),
-            y.checked_add(1)
-                .filter(|&y| y < self.height)
-                .map(|y| Location { x, y }),
-        ]
-        .into_iter()
-        .flatten()
-    }
-
-    fn step_round(&mut self) -> RoundResult {
+    /// Gives every unit a turn to move and attack.
+    ///
+    /// ```
+    /// use aoc2018::day15::Map;
+    ///
+    /// let mut map = Map::builder().parse("#####\n#GE.#\n#####").unwrap();
+    /// let result = map.step_round();
+    /// assert!(!result.finished);
+    /// assert_eq!(1, map.round());
+    /// assert_eq!(vec![197, 197], map.units().map(|(_, unit)| unit.health).collect::<Vec<_>>());
+    /// ```
+    pub fn step_round(&mut self) -> RoundResult {
         let mut elves_killed = 0;
+        let (mut searches, mut nodes_expanded) = (0, 0);
+        let mut search = mem::take(&mut self.search);
         for mut unit_loc in self.units.keys().copied().collect::<Vec<_>>() {
+            search.clear();
             let unit = match self.units.remove(&unit_loc) {
                 Some(unit) => unit,
                 None => continue,
@@ -149,68 +181,42 @@ impl Map {
                 .iter()
                 .filter(|(_, target)| target.allegiance != unit.allegiance)
                 .inspect(|_| targets_remain = true)
-                .flat_map(|(&location, _)| self.adjacent_locations(location))
+                .flat_map(|(&location, _)| self.walls.neighbours4(location))
                 .filter(|&location| self.get(location).is_open() || location == unit_loc)
                 .collect();
 
             if !targets_remain {
                 self.units.insert(unit_loc, unit);
+                self.search = search;
                 return RoundResult {
                     finished: true,
                     elves_killed,
+                    searches,
+                    nodes_expanded,
                 };
             }
 
             // Move
             if !in_range.contains(&unit_loc) {
-                let mut best = None;
-                let mut best_step = None;
-                let mut distances = vec![vec![u32::MAX; self.width]; self.height];
-                distances[unit_loc.y][unit_loc.x] = 0;
-                for first_step in self.adjacent_locations(unit_loc) {
-                    if !self.get(first_step).is_open() {
-                        continue;
-                    }
-                    let mut min = None;
-                    let mut open_set = BinaryHeap::new();
-                    open_set.push(Reverse((1, first_step)));
-                    while let Some(Reverse((dist, step))) = open_set.pop() {
-                        if min.map(|(min_dist, _)| dist > min_dist).unwrap_or(false) {
-                            break;
-                        }
-                        if in_range.contains(&step)
-                            && min.map(|(_, min_dest)| step < min_dest).unwrap_or(true)
-                        {
-                            min = Some((dist, step));
-                        }
-                        for neighbour in self.adjacent_locations(step) {
-                            if !self.get(neighbour).is_open() {
-                                continue;
-                            }
-                            if dist + 1 < distances[neighbour.y][neighbour.x] {
-                                distances[neighbour.y][neighbour.x] = dist + 1;
-                                open_set.push(Reverse((dist + 1, neighbour)));
-                            }
-                        }
-                    }
-                    match (min, best) {
-                        (Some((min_dist, min_dest)), Some((best_dist, best_dest))) => {
-                            if min_dist < best_dist
-                                || (min_dist == best_dist && min_dest < best_dest)
-                            {
-                                best = min;
-                                best_step = Some(first_step);
-                            }
-                        }
-                        (Some(_), None) => {
-                            best = min;
-                            best_step = Some(first_step);
-                        }
-                        (None, _) => {}
-                    }
-                }
-                if let Some(best_step) = best_step {
-                    unit_loc = best_step;
+                let open_neighbours = |location| {
+                    self.walls
+                        .neighbours4(location)
+                        .filter(|&neighbour| self.get(neighbour).is_open())
+                };
+                searches += 1;
+                let target = search.bfs([unit_loc], open_neighbours, |location| {
+                    in_range.contains(&location)
+                });
+                nodes_expanded += search.expanded();
+                if let Some(target) = target {
+                    // Searching back from the target finds the squares next
+                    // to the unit that are closest to it in reading order.
+                    searches += 1;
+                    let first_step = search.bfs([target.node], open_neighbours, |location| {
+                        location.manhattan_distance(unit_loc) == 1
+                    });
+                    nodes_expanded += search.expanded();
+                    unit_loc = first_step.expect("the unit can reach its target").node;
                 }
             }
             let old = self.units.insert(unit_loc, unit);
@@ -219,7 +225,8 @@ impl Map {
 
             // Attack
             let weakest_enemy = self
-                .adjacent_locations(unit_loc)
+                .walls
+                .neighbours4(unit_loc)
                 .filter_map(|loc| {
                     self.get(loc)
                         .unit()
@@ -244,38 +251,66 @@ impl Map {
             }
         }
         self.round += 1;
+        self.search = search;
         RoundResult {
             finished: false,
             elves_killed,
+            searches,
+            nodes_expanded,
         }
     }
 
-    fn score(&self) -> u32 {
-        let remaining_health: u32 = self.units.iter().map(|(_, unit)| unit.health as u32).sum();
+    /// The battle's outcome: full rounds completed times the remaining health.
+    pub fn score(&self) -> u32 {
+        let remaining_health: u32 = self.units.values().map(|unit| unit.health as u32).sum();
         self.round * remaining_health
     }
+
+    /// Draws the walls, floor and units as they are now.
+    #[cfg(feature = "render")]
+    pub fn render(&self) -> Image {
+        self.walls.map(|location, _| match self.get(location) {
+            Tile::Wall => palette::WALL,
+            Tile::Floor(None) => palette::FLOOR,
+            Tile::Floor(Some(unit)) => match unit.allegiance {
+                Allegiance::Elf => palette::ELF,
+                Allegiance::Goblin => palette::GOBLIN,
+            },
+        })
+    }
 }
 
 impl fmt::Display for Map {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for y in 0..self.height {
-            for x in 0..self.width {
-                fmt::Display::fmt(&self.get(Location { x, y }), f)?;
-            }
-            f.write_char('\n')?;
-        }
-        Ok(())
+        let tiles = self.walls.map(|location, _| self.get(location));
+        fmt::Display::fmt(&tiles, f)
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-struct RoundResult {
-    finished: bool,
-    elves_killed: usize,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundResult {
+    /// Whether the round ended early because a unit found no targets left.
+    pub finished: bool,
+    pub elves_killed: usize,
+    /// How many breadth-first searches units made to choose their moves.
+    pub searches: usize,
+    /// How many squares those searches visited.
+    pub nodes_expanded: usize,
+}
+
+impl RoundResult {
+    /// Reports the round's searches to `ctx`'s metrics.
+    fn measure(&self, ctx: &Context) {
+        ctx.count("rounds", 1);
+        ctx.count("searches", self.searches as u64);
+        ctx.count("nodes expanded", self.nodes_expanded as u64);
+        ctx.record("nodes expanded per round", self.nodes_expanded as u64);
+    }
 }
 
-#[derive(Debug)]
-struct MapBuilder {
+/// Sets the attack power of each side before parsing a [`Map`].
+#[derive(Debug, Clone)]
+pub struct MapBuilder {
     elf_attack: u8,
     goblin_attack: u8,
 }
@@ -290,79 +325,141 @@ impl Default for MapBuilder {
 }
 
 impl MapBuilder {
-    fn elf_attack(&mut self, attack: u8) -> &mut Self {
+    pub fn elf_attack(&mut self, attack: u8) -> &mut Self {
         self.elf_attack = attack;
         self
     }
 
-    fn parse(&self, s: &str) -> Result<Map> {
-        let mut grid = BitVec::with_capacity(s.len());
+    pub fn goblin_attack(&mut self, attack: u8) -> &mut Self {
+        self.goblin_attack = attack;
+        self
+    }
+
+    /// Parses a map of `#` walls, `.` open floor and `E`/`G` units, all of
+    /// which start with 200 health.
+    pub fn parse(&self, s: &str) -> Result<Map> {
         let mut units = BTreeMap::new();
-        let width = s.lines().next().ok_or_else(|| eyre!("map is empty"))?.len();
-        let mut height = 0;
-        for (y, line) in s.lines().enumerate() {
-            if line.len() != width {
-                bail!(
-                    "Map must be rectangular, line {} is a different length to the first",
-                    y + 1
-                )
-            }
-            height += 1;
-            for (x, c) in line.chars().enumerate() {
-                match c {
-                    '#' => grid.push(true),
-                    '.' => grid.push(false),
-                    'E' => {
-                        units.insert(
-                            Location { x, y },
-                            Unit {
-                                allegiance: Allegiance::Elf,
-                                health: 200,
-                                attack: self.elf_attack,
-                            },
-                        );
-                        grid.push(false)
-                    }
-                    'G' => {
-                        units.insert(
-                            Location { x, y },
-                            Unit {
-                                allegiance: Allegiance::Goblin,
-                                health: 200,
-                                attack: self.goblin_attack,
-                            },
-                        );
-                        grid.push(false)
-                    }
-                    _ => bail!("Unexpected character in map {:?}", c),
-                }
-            }
-        }
+        let walls = Grid::parse(s, |location, c| {
+            let allegiance = match c {
+                '#' => return Ok(true),
+                '.' => return Ok(false),
+                'E' => Allegiance::Elf,
+                'G' => Allegiance::Goblin,
+                _ => bail!("Unexpected character {:?} in map at {}", c, location),
+            };
+            let attack = match allegiance {
+                Allegiance::Elf => self.elf_attack,
+                Allegiance::Goblin => self.goblin_attack,
+            };
+            units.insert(
+                location,
+                Unit {
+                    allegiance,
+                    health: 200,
+                    attack,
+                },
+            );
+            Ok(false)
+        })?;
         Ok(Map {
             round: 0,
-            width,
-            height,
-            grid,
+            walls,
             units,
+            search: Search::new(),
         })
     }
 }
 
-#[instrument(skip(input))]
-fn part1(input: &str) -> Result<String> {
+/// Measures the map and counts each side's units.
+fn inspect(input: &str) -> Result<Vec<(&'static str, Fact)>> {
+    let map = Map::builder().parse(input)?;
+    let count = |allegiance| {
+        map.units()
+            .filter(|(_, unit)| unit.allegiance == allegiance)
+            .count()
+    };
+    let open = map.walls.iter().filter(|(_, &wall)| !wall).count();
+    Ok(vec![
+        ("width", map.width().into()),
+        ("height", map.height().into()),
+        ("open squares", open.into()),
+        ("elves", count(Allegiance::Elf).into()),
+        ("goblins", count(Allegiance::Goblin).into()),
+    ])
+}
+
+/// Shows the map after every round of the part 1 battle, listing each row's
+/// units and their health beside it.
+#[cfg(feature = "animate")]
+fn animate(input: &str) -> Result<Frames> {
     let mut map = Map::builder().parse(input)?;
-    while !map.step_round().finished {}
+    let (mut finished, mut shown_end) = (false, false);
+    Ok(Box::new(std::iter::from_fn(move || {
+        if shown_end {
+            return None;
+        }
+        let mut frame = format!("After {} rounds:\n", map.round());
+        for (y, row) in map.to_string().lines().enumerate() {
+            frame += row;
+            let units = map.units().filter(|(location, _)| location.y == y);
+            for (i, (_, unit)) in units.enumerate() {
+                let separator = if i == 0 { "   " } else { ", " };
+                frame += &format!("{}{}({})", separator, unit, unit.health);
+            }
+            frame.push('\n');
+        }
+        if finished {
+            shown_end = true;
+        } else {
+            finished = map.step_round().finished;
+        }
+        Some(Ok(frame))
+    })))
+}
+
+/// Draws the map as it stands once the part 1 battle is over.
+#[cfg(feature = "render")]
+fn render(input: &str, ctx: &Context) -> Result<Image> {
+    let mut map = Map::builder().parse(input)?;
+    while !map.step_round().finished {
+        ctx.check()?;
+    }
+    Ok(map.render())
+}
+
+#[instrument(skip(input, ctx))]
+fn part1(input: &str, ctx: &Context) -> Result<String> {
+    let mut map = Map::builder().parse(input)?;
+    loop {
+        ctx.checkpoint(|| format!("round {}", map.round))?;
+        let result = map.step_round();
+        result.measure(ctx);
+        if result.finished {
+            break;
+        }
+    }
+    explain_outcome(&map, ctx);
     Ok(format!("{}", map.score()))
 }
 
-#[instrument(skip(input))]
-fn part2(input: &str) -> Result<String> {
-    let mut attack = 3;
+/// Reports how the battle ended.
+fn explain_outcome(map: &Map, ctx: &Context) {
+    ctx.explain("rounds", map.round());
+    let remaining_health: u32 = map.units().map(|(_, unit)| u32::from(unit.health)).sum();
+    ctx.explain("remaining health", remaining_health);
+}
+
+#[instrument(skip(input, ctx))]
+fn part2(input: &str, ctx: &Context) -> Result<String> {
+    let mut attack: u8 = ctx.param(&MIN_ATTACK)?;
     let map = loop {
-        attack += 1;
+        ctx.progress("attack power", attack.into(), None);
         let mut map = Map::builder().elf_attack(attack).parse(input)?;
         let succeeded = loop {
+            ctx.checkpoint(|| format!("attack power {}, round {}", attack, map.round))?;
+            ctx.progress("round", map.round.into(), None);
             let result = map.step_round();
+            result.measure(ctx);
             if result.elves_killed > 0 {
                 break false;
             } else if result.finished {
@@ -372,13 +469,24 @@ fn part2(input: &str) -> Result<String> {
         if succeeded {
             break map;
         }
+        attack = attack
+            .checked_add(1)
+            .ok_or_else(|| eyre!("The elves lose with any attack power"))?;
     };
+    ctx.explain("elf attack power", attack);
+    explain_outcome(&map, ctx);
     Ok(format!("{}", map.score()))
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{fmt::Write, sync::Arc};
+
     use super::*;
+    use crate::{
+        testing::{assert_snapshot, RecordFacts},
+        Fact,
+    };
 
     #[test]
     fn movement() {
@@ -394,83 +502,26 @@ mod tests {
             #########\n\
         ";
         let mut map: Map = Map::builder().parse(MAP).unwrap();
-        println!("{}", map);
         assert_eq!(MAP, format!("{}", map));
-        map.step_round();
-        println!("{}", map);
-        assert_eq!(
-            "\
-            #########\n\
-            #.G...G.#\n\
-            #...G...#\n\
-            #...E..G#\n\
-            #.G.....#\n\
-            #.......#\n\
-            #G..G..G#\n\
-            #.......#\n\
-            #########\n\
-            ",
-            format!("{}", map)
-        );
-        map.step_round();
-        println!("{}", map);
-        assert_eq!(
-            "\
-            #########\n\
-            #..G.G..#\n\
-            #...G...#\n\
-            #.G.E.G.#\n\
-            #.......#\n\
-            #G..G..G#\n\
-            #.......#\n\
-            #.......#\n\
-            #########\n\
-            ",
-            format!("{}", map)
-        );
-        map.step_round();
-        println!("{}", map);
-        assert_eq!(
-            "\
-            #########\n\
-            #.......#\n\
-            #..GGG..#\n\
-            #..GEG..#\n\
-            #G..G...#\n\
-            #......G#\n\
-            #.......#\n\
-            #.......#\n\
-            #########\n\
-            ",
-            format!("{}", map)
-        );
+        let mut snapshot = String::new();
+        for round in 1..=3 {
+            map.step_round();
+            write!(snapshot, "after round {}:\n{}", round, map).unwrap();
+        }
+        assert_snapshot("day15_movement", &snapshot);
     }
 
     #[test]
     fn test_part1() {
-        const MAP: &str = "\
-            #######\n\
-            #.G...#\n\
-            #...EG#\n\
-            #.#.#G#\n\
-            #..G#E#\n\
-            #.....#\n\
-            #######\n\
-        ";
-        assert_eq!("27730", part1(MAP).unwrap());
+        assert_eq!("27730", part1(EXAMPLE, &Context::default()).unwrap());
     }
 
     #[test]
     fn test_part2() {
-        const MAP: &str = "\
-            #######\n\
-            #.G...#\n\
-            #...EG#\n\
-            #.#.#G#\n\
-            #..G#E#\n\
-            #.....#\n\
-            #######\n\
-        ";
-        assert_eq!("4988", part2(MAP).unwrap());
+        let facts = Arc::new(RecordFacts::default());
+        let ctx = Context::default().with_explain(facts.clone());
+        assert_eq!("4988", part2(EXAMPLE, &ctx).unwrap());
+        assert_eq!(Some(Fact::Int(15)), facts.get("elf attack power"));
+        assert_eq!(Some(Fact::Int(29)), facts.get("rounds"));
     }
 }
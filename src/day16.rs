@@ -1,21 +1,30 @@
-use std::{convert::TryInto, str::FromStr};
+use std::{collections::BTreeSet, convert::TryInto, str::FromStr};
 
 use bitvec::prelude::*;
 use enum_iterator::{all, Sequence};
-use eyre::{bail, eyre, Context, Result};
+use eyre::{bail, eyre, Result, WrapErr};
 use tracing::instrument;
 
-use crate::Solution;
+use crate::{Context, Fact, Solution, Variant};
 
 pub const SOLUTION: Solution = Solution {
     input: include_str!("../input/day16.txt"),
-    part1,
-    part2,
+    part1: &[Variant::new("default", part1)],
+    part2: &[Variant::new("default", part2)],
+    #[cfg(feature = "render")]
+    render: None,
+    #[cfg(feature = "animate")]
+    animate: None,
+    inspect: Some(inspect),
+    params: &[],
+    examples: &[],
 };
 
-#[derive(Debug, Clone, Copy, Sequence)]
+/// The sixteen operations of the wrist device, named after the puzzle's
+/// mnemonics: `R` operands are registers and `I` operands are immediate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Sequence)]
 #[repr(u8)]
-enum OpCode {
+pub enum OpCode {
     AddR,
     AddI,
     MulR,
@@ -34,20 +43,33 @@ enum OpCode {
     EqRR,
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Instruction {
-    opcode: OpCode,
-    input_a: u16,
-    input_b: u16,
-    output_c: u16,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub opcode: OpCode,
+    pub input_a: u16,
+    pub input_b: u16,
+    pub output_c: u16,
 }
 
-#[derive(Debug, Clone, Copy)]
-struct UnknownInstruction {
-    opcode: u8,
-    input_a: u16,
-    input_b: u16,
-    output_c: u16,
+/// An instruction whose opcode is still a number, parsed from `9 2 1 2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownInstruction {
+    pub opcode: u8,
+    pub input_a: u16,
+    pub input_b: u16,
+    pub output_c: u16,
+}
+
+impl UnknownInstruction {
+    /// Decodes this instruction using a table from opcode number to operation.
+    pub fn resolve(self, opcodes: &[OpCode; 16]) -> Instruction {
+        Instruction {
+            opcode: opcodes[self.opcode as usize],
+            input_a: self.input_a,
+            input_b: self.input_b,
+            output_c: self.output_c,
+        }
+    }
 }
 
 impl FromStr for UnknownInstruction {
@@ -80,13 +102,28 @@ impl FromStr for UnknownInstruction {
     }
 }
 
+/// The device's four registers, parsed from `[3, 2, 1, 1]`.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
-struct Cpu {
-    registers: [u16; 4],
+pub struct Cpu {
+    pub registers: [u16; 4],
 }
 
 impl Cpu {
-    fn execute(
+    /// Runs one instruction, returning the new register values.
+    ///
+    /// ```
+    /// use aoc2018::day16::{Cpu, Instruction, OpCode};
+    ///
+    /// let cpu: Cpu = "[3, 2, 1, 1]".parse().unwrap();
+    /// let cpu = cpu.execute(Instruction {
+    ///     opcode: OpCode::MulR,
+    ///     input_a: 2,
+    ///     input_b: 1,
+    ///     output_c: 2,
+    /// });
+    /// assert_eq!([3, 2, 2, 1], cpu.registers);
+    /// ```
+    pub fn execute(
         mut self,
         Instruction {
             opcode,
@@ -135,15 +172,17 @@ impl FromStr for Cpu {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Sample {
-    before: Cpu,
-    instruction: UnknownInstruction,
-    after: Cpu,
+/// An observation of an unknown instruction's effect on the registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    pub before: Cpu,
+    pub instruction: UnknownInstruction,
+    pub after: Cpu,
 }
 
 impl Sample {
-    fn num_possible_ops(self) -> usize {
+    /// How many operations are consistent with this sample.
+    pub fn num_possible_ops(self) -> usize {
         all::<OpCode>()
             .filter(|opcode| {
                 let result = self.before.execute(Instruction {
@@ -158,10 +197,11 @@ impl Sample {
     }
 }
 
-#[derive(Debug)]
-struct Input {
-    samples: Vec<Sample>,
-    program: Vec<UnknownInstruction>,
+/// The puzzle input: the samples, then a test program.
+#[derive(Debug, Clone)]
+pub struct Input {
+    pub samples: Vec<Sample>,
+    pub program: Vec<UnknownInstruction>,
 }
 
 impl FromStr for Input {
@@ -170,7 +210,11 @@ impl FromStr for Input {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut samples = Vec::new();
         let mut lines = s.lines().peekable();
-        while lines.peek().map(|line| !line.is_empty()).unwrap_or(false) {
+        while lines
+            .peek()
+            .map(|line| line.starts_with("Before:"))
+            .unwrap_or(false)
+        {
             let before = lines.next().unwrap();
             let before = before
                 .strip_prefix("Before:")
@@ -203,15 +247,31 @@ impl FromStr for Input {
                 after,
             });
         }
-        lines.next();
-        lines.next();
-        let program = lines.map(|line| line.parse()).collect::<Result<_>>()?;
+        let program = lines
+            .skip_while(|line| line.trim().is_empty())
+            .map(|line| line.parse())
+            .collect::<Result<_>>()?;
         Ok(Self { samples, program })
     }
 }
 
-#[instrument(skip(input))]
-fn part1(input: &str) -> Result<String> {
+/// Counts the samples and the instructions in the test program.
+fn inspect(input: &str) -> Result<Vec<(&'static str, Fact)>> {
+    let input: Input = input.parse()?;
+    let sampled: BTreeSet<_> = input
+        .samples
+        .iter()
+        .map(|sample| sample.instruction.opcode)
+        .collect();
+    Ok(vec![
+        ("samples", input.samples.len().into()),
+        ("opcodes sampled", sampled.len().into()),
+        ("program length", input.program.len().into()),
+    ])
+}
+
+#[instrument(skip(input, _ctx))]
+fn part1(input: &str, _ctx: &Context) -> Result<String> {
     let input: Input = input.parse()?;
     let answer = input
         .samples
@@ -223,11 +283,29 @@ fn part1(input: &str) -> Result<String> {
 
 type OpcodeSet = BitArr!(for 16, in u16);
 
-#[instrument(skip(input))]
-fn part2(input: &str) -> Result<String> {
+#[instrument(skip(input, ctx))]
+fn part2(input: &str, ctx: &Context) -> Result<String> {
     let input: Input = input.parse()?;
+    let opcodes = resolve_opcodes(&input.samples)?;
+    if ctx.explaining() {
+        let table = opcodes
+            .iter()
+            .enumerate()
+            .map(|(number, op)| (number, format!("{:?}", op).to_lowercase()));
+        ctx.explain("opcodes", Fact::table(table));
+    }
+    let mut cpu = Cpu::default();
+    for instruction in input.program {
+        cpu = cpu.execute(instruction.resolve(&opcodes));
+    }
+    Ok(cpu.registers[0].to_string())
+}
+
+/// Works out which operation each opcode number performs, by elimination
+/// over the samples.
+pub fn resolve_opcodes(samples: &[Sample]) -> Result<[OpCode; 16]> {
     let mut possible_opcodes: [OpcodeSet; 16] = [bitarr![u16, LocalBits; 1; 16]; 16];
-    for sample in input.samples {
+    for sample in samples {
         for (i, opcode) in all::<OpCode>().enumerate() {
             if sample.after
                 != sample.before.execute(Instruction {
@@ -241,29 +319,23 @@ fn part2(input: &str) -> Result<String> {
             }
         }
     }
-    let mut opcode_map = [OpCode::AddI; 16];
+    let mut opcode_map = [None; 16];
     for _ in 0..16 {
         for i in 0..16 {
             let opcode = possible_opcodes[i];
             if opcode.count_ones() == 1 {
-                opcode_map[i] = all::<OpCode>().nth(opcode.leading_zeros()).unwrap();
+                opcode_map[i] = all::<OpCode>().nth(opcode.leading_zeros());
                 for possible_opcodes in &mut possible_opcodes {
                     *possible_opcodes &= !opcode;
                 }
             }
         }
     }
-
-    let mut cpu = Cpu::default();
-    for instruction in input.program {
-        cpu = cpu.execute(Instruction {
-            opcode: opcode_map[instruction.opcode as usize],
-            input_a: instruction.input_a,
-            input_b: instruction.input_b,
-            output_c: instruction.output_c,
-        });
+    let mut resolved = [OpCode::AddR; 16];
+    for (i, opcode) in opcode_map.into_iter().enumerate() {
+        resolved[i] = opcode.ok_or_else(|| eyre!("Could not work out what opcode {} does", i))?;
     }
-    Ok(cpu.registers[0].to_string())
+    Ok(resolved)
 }
 
 #[cfg(test)]
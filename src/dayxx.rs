@@ -1,20 +1,27 @@
 use eyre::Result;
 use tracing::instrument;
 
-use crate::Solution;
+use crate::{Context, Solution, Variant};
 
 pub const SOLUTION: Solution = Solution {
     input: include_str!("../input/dayxx.txt"),
-    part1,
-    part2,
+    part1: &[Variant::new("default", part1)],
+    part2: &[Variant::new("default", part2)],
+    #[cfg(feature = "render")]
+    render: None,
+    #[cfg(feature = "animate")]
+    animate: None,
+    inspect: None,
+    params: &[],
+    examples: &[],
 };
 
-#[instrument(skip(input))]
-fn part1(input: &str) -> Result<String> {
+#[instrument(skip(input, _ctx))]
+fn part1(input: &str, _ctx: &Context) -> Result<String> {
     Ok("unsolved".to_string())
 }
 
-#[instrument(skip(input))]
-fn part2(input: &str) -> Result<String> {
+#[instrument(skip(input, _ctx))]
+fn part2(input: &str, _ctx: &Context) -> Result<String> {
     Ok("unsolved".to_string())
 }
@@ -0,0 +1,139 @@
+use std::fmt;
+
+use serde::{ser::SerializeMap, Serialize, Serializer};
+
+/// Receives the intermediate results a solution reports about how it reached
+/// its answer.
+pub trait Explain: Send + Sync {
+    fn fact(&self, name: &'static str, value: Fact);
+}
+
+/// Summarizes a parsed input, such as how many claims there are on day 3,
+/// failing if the input does not parse.
+pub type Inspect = fn(&str) -> eyre::Result<Vec<(&'static str, Fact)>>;
+
+/// An intermediate result, such as the guard chosen on day 4.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fact {
+    Int(i64),
+    Text(String),
+    List(Vec<Fact>),
+    /// Named fields, in the order they should be shown.
+    Table(Vec<(String, Fact)>),
+}
+
+impl Fact {
+    /// A table from `(name, value)` pairs.
+    pub fn table<K, V>(fields: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: ToString,
+        V: Into<Fact>,
+    {
+        Fact::Table(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+macro_rules! int_fact {
+    ($($int:ty),*) => {$(
+        impl From<$int> for Fact {
+            fn from(value: $int) -> Self {
+                Fact::Int(value as i64)
+            }
+        }
+    )*};
+}
+
+int_fact!(i8, i16, i32, i64, u8, u16, u32, u64, usize, isize);
+
+impl From<char> for Fact {
+    fn from(value: char) -> Self {
+        Fact::Text(value.to_string())
+    }
+}
+
+impl From<&str> for Fact {
+    fn from(value: &str) -> Self {
+        Fact::Text(value.to_string())
+    }
+}
+
+impl From<String> for Fact {
+    fn from(value: String) -> Self {
+        Fact::Text(value)
+    }
+}
+
+impl<T: Into<Fact>> From<Vec<T>> for Fact {
+    fn from(values: Vec<T>) -> Self {
+        Fact::List(values.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Shows the fact on one line, e.g. `{guard: 10, minutes: [24, 25]}`.
+impl fmt::Display for Fact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fact::Int(value) => write!(f, "{}", value),
+            Fact::Text(value) => f.write_str(value),
+            Fact::List(values) => {
+                f.write_str("[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                f.write_str("]")
+            }
+            Fact::Table(fields) => {
+                f.write_str("{")?;
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}: {}", name, value)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+impl Serialize for Fact {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Fact::Int(value) => serializer.serialize_i64(*value),
+            Fact::Text(value) => serializer.serialize_str(value),
+            Fact::List(values) => values.serialize(serializer),
+            Fact::Table(fields) => {
+                let mut map = serializer.serialize_map(Some(fields.len()))?;
+                for (name, value) in fields {
+                    map.serialize_entry(name, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let fact = Fact::table([("guard", Fact::from(10)), ("minutes", vec![24, 25].into())]);
+        assert_eq!("{guard: 10, minutes: [24, 25]}", fact.to_string());
+    }
+
+    #[test]
+    fn json_keeps_table_order() {
+        let fact = Fact::table([("b", 1), ("a", 2)]);
+        assert_eq!(r#"{"b":1,"a":2}"#, serde_json::to_string(&fact).unwrap());
+    }
+}
@@ -0,0 +1,303 @@
+//! A C interface to [`SOLUTIONS`], built into the crate's `cdylib` so that
+//! other languages can solve days without spawning the binary.
+//!
+//! `include/aoc2018.h` declares these functions and is generated by
+//! [`header`]; a test fails if it is out of date, and running the tests with
+//! `AOC2018_BLESS=1` rewrites it.
+
+use std::{
+    cell::RefCell,
+    ffi::c_char,
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+    time::Duration,
+};
+
+use eyre::{bail, eyre, Result};
+
+use crate::{input, Context, SOLUTIONS};
+
+/// Returned by [`aoc2018_solve`] when solving fails, after which
+/// [`aoc2018_last_error`] says why.
+pub const AOC2018_ERROR: i64 = -1;
+
+/// How long [`aoc2018_solve`] lets a solution run before giving up.
+pub const AOC2018_DEFAULT_TIMEOUT_MS: u64 = 60_000;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Solves `part` (1 or 2) of `day` for the UTF-8 `input` with the default
+/// variant, and copies the answer into `out` as a NUL-terminated string.
+///
+/// Solving gives up after [`AOC2018_DEFAULT_TIMEOUT_MS`]; see
+/// [`aoc2018_solve_with_timeout`] to choose another limit.
+///
+/// Like `snprintf`, this returns the length of the whole answer and writes
+/// as much of it as fits in `out_len - 1` bytes, so a return value of
+/// `out_len` or more means the answer was cut short. Returns
+/// [`AOC2018_ERROR`] if solving failed.
+///
+/// # Safety
+///
+/// `input` must point to `input_len` readable bytes, and `out` to `out_len`
+/// writable bytes. `out` may be null if `out_len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn aoc2018_solve(
+    day: u32,
+    part: u32,
+    input: *const u8,
+    input_len: usize,
+    out: *mut c_char,
+    out_len: usize,
+) -> i64 {
+    aoc2018_solve_with_timeout(
+        day,
+        part,
+        input,
+        input_len,
+        AOC2018_DEFAULT_TIMEOUT_MS,
+        out,
+        out_len,
+    )
+}
+
+/// Like [`aoc2018_solve`], but gives up after `timeout_ms` milliseconds, or
+/// never if it is 0.
+///
+/// # Safety
+///
+/// The same as for [`aoc2018_solve`].
+#[no_mangle]
+pub unsafe extern "C" fn aoc2018_solve_with_timeout(
+    day: u32,
+    part: u32,
+    input: *const u8,
+    input_len: usize,
+    timeout_ms: u64,
+    out: *mut c_char,
+    out_len: usize,
+) -> i64 {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        if input.is_null() && input_len > 0 {
+            bail!("The input is null");
+        }
+        let input = if input_len == 0 {
+            &[]
+        } else {
+            slice::from_raw_parts(input, input_len)
+        };
+        let timeout = (timeout_ms > 0).then(|| Duration::from_millis(timeout_ms));
+        solve(day, part, input, timeout)
+    }))
+    .unwrap_or_else(|_| Err(eyre!("The solution panicked")));
+    match result {
+        Ok(answer) => {
+            LAST_ERROR.with(|last| *last.borrow_mut() = None);
+            copy_out(&answer, out, out_len)
+        }
+        Err(error) => {
+            LAST_ERROR.with(|last| *last.borrow_mut() = Some(format!("{:#}", error)));
+            AOC2018_ERROR
+        }
+    }
+}
+
+/// Copies the message of the last error on this thread into `out`, in the
+/// same way as [`aoc2018_solve`] copies answers, and returns its length. This
+/// is 0 if the last call succeeded.
+///
+/// # Safety
+///
+/// `out` must point to `out_len` writable bytes, and may be null if `out_len`
+/// is 0.
+#[no_mangle]
+pub unsafe extern "C" fn aoc2018_last_error(out: *mut c_char, out_len: usize) -> i64 {
+    LAST_ERROR.with(|last| copy_out(last.borrow().as_deref().unwrap_or(""), out, out_len))
+}
+
+fn solve(day: u32, part: u32, input: &[u8], timeout: Option<Duration>) -> Result<String> {
+    let solution = match day.checked_sub(1).and_then(|i| SOLUTIONS.get(i as usize)) {
+        Some(solution) if solution.is_solved() => solution,
+        _ => bail!("Day {} is not solved", day),
+    };
+    let variants = match part {
+        1 => solution.part1,
+        2 => solution.part2,
+        _ => bail!("Part {} does not exist", part),
+    };
+    let input = std::str::from_utf8(input).map_err(|_| eyre!("The input is not valid UTF-8"))?;
+    let ctx = Context::default();
+    if let Some(timeout) = timeout {
+        ctx.cancel_token().cancel_after(timeout);
+    }
+    (variants[0].run)(&input::normalize(input), &ctx)
+}
+
+/// Copies as much of `text` as fits into `out` followed by a NUL.
+unsafe fn copy_out(text: &str, out: *mut c_char, out_len: usize) -> i64 {
+    if out_len > 0 && !out.is_null() {
+        let len = text.len().min(out_len - 1);
+        ptr::copy_nonoverlapping(text.as_ptr(), out.cast(), len);
+        *out.add(len) = 0;
+    }
+    text.len() as i64
+}
+
+/// The C header declaring this interface.
+pub fn header() -> String {
+    format!(
+        r#"/* Generated by `aoc2018::ffi::header`, do not edit. */
+#ifndef AOC2018_H
+#define AOC2018_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {{
+#endif
+
+/* The number of days, some of which may be unsolved. */
+#define AOC2018_DAYS {days}
+
+/* Returned by aoc2018_solve when solving fails. */
+#define AOC2018_ERROR {error}
+
+/* How many milliseconds aoc2018_solve lets a solution run before giving up. */
+#define AOC2018_DEFAULT_TIMEOUT_MS {timeout}
+
+/*
+ * Solves `part` (1 or 2) of `day` for the UTF-8 `input` with the default
+ * variant, and copies the answer into `out` as a NUL-terminated string.
+ * Solving gives up after AOC2018_DEFAULT_TIMEOUT_MS.
+ *
+ * Like snprintf, returns the length of the whole answer, which was cut short
+ * if it is `out_len` or more. Returns AOC2018_ERROR if solving failed.
+ */
+int64_t aoc2018_solve(uint32_t day, uint32_t part, const uint8_t *input,
+                      size_t input_len, char *out, size_t out_len);
+
+/*
+ * Like aoc2018_solve, but gives up after `timeout_ms` milliseconds, or never
+ * if it is 0.
+ */
+int64_t aoc2018_solve_with_timeout(uint32_t day, uint32_t part,
+                                   const uint8_t *input, size_t input_len,
+                                   uint64_t timeout_ms, char *out,
+                                   size_t out_len);
+
+/*
+ * Copies the message of the last error on this thread into `out` and returns
+ * its length, which is 0 if the last call succeeded.
+ */
+int64_t aoc2018_last_error(char *out, size_t out_len);
+
+#ifdef __cplusplus
+}}
+#endif
+
+#endif
+"#,
+        days = SOLUTIONS.len(),
+        error = AOC2018_ERROR,
+        timeout = AOC2018_DEFAULT_TIMEOUT_MS,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use super::*;
+    use crate::testing::BLESS_VAR;
+
+    const HEADER_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/include/aoc2018.h");
+
+    #[test]
+    fn header_is_up_to_date() {
+        if env::var_os(BLESS_VAR).is_some() {
+            fs::write(HEADER_PATH, header()).unwrap();
+        }
+        assert!(
+            fs::read_to_string(HEADER_PATH).unwrap() == header(),
+            "{} is out of date, run the tests with {}=1 to regenerate it",
+            HEADER_PATH,
+            BLESS_VAR
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let mut out = [0 as c_char; 64];
+        let input = b"+1\n";
+        let solved = unsafe { aoc2018_solve(26, 1, input.as_ptr(), 3, out.as_mut_ptr(), 64) };
+        assert_eq!(AOC2018_ERROR, solved);
+        let len = unsafe { aoc2018_last_error(out.as_mut_ptr(), out.len()) };
+        let message = unsafe { std::ffi::CStr::from_ptr(out.as_ptr()) };
+        assert_eq!(Ok("Day 26 is not solved"), message.to_str());
+        assert_eq!(message.to_bytes().len() as i64, len);
+    }
+
+    /// Builds the cdylib and returns its path. Building it here rather than
+    /// relying on the one cargo may have built alongside the tests means the
+    /// C program never links a stale library.
+    #[cfg(all(unix, feature = "day01"))]
+    fn build_library() -> std::path::PathBuf {
+        use std::process::Command;
+
+        let output = Command::new(env!("CARGO"))
+            .args(["build", "--lib", "--message-format=json"])
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "Building the library failed:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let library = String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+            .filter(|message| message["reason"] == "compiler-artifact")
+            .filter(|message| message["target"]["name"] == "aoc2018")
+            .flat_map(|message| message["filenames"].as_array().cloned().unwrap_or_default())
+            .filter_map(|file| file.as_str().map(std::path::PathBuf::from))
+            .find(|file| file.to_string_lossy().ends_with(env::consts::DLL_SUFFIX));
+        library.expect("cargo built no dynamic library")
+    }
+
+    /// Compiles and runs `include/test.c` against a freshly built cdylib.
+    #[test]
+    #[cfg(all(unix, feature = "day01"))]
+    fn c_program() {
+        use std::{path::Path, process::Command};
+
+        let library = build_library();
+        let dir = library.parent().unwrap();
+        let include = Path::new(HEADER_PATH).parent().unwrap();
+        let program = env::current_exe()
+            .unwrap()
+            .with_file_name("aoc2018-ffi-test");
+        let status = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+            .arg(include.join("test.c"))
+            .arg("-I")
+            .arg(include)
+            .arg("-o")
+            .arg(&program)
+            .arg(&library)
+            .arg(format!("-Wl,-rpath,{}", dir.display()))
+            .status()
+            .expect("a C compiler is needed to test the C interface");
+        assert!(status.success(), "Compiling test.c failed");
+        let output = Command::new(&program).output().unwrap();
+        assert!(
+            output.status.success(),
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
@@ -0,0 +1,426 @@
+//! Rectangular grids addressed by [`Point`]s, shared by the map-based days.
+
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display, Write},
+    ops::{Index, IndexMut},
+};
+
+use eyre::{bail, eyre, Result};
+
+/// A position on a grid, with `x` increasing rightwards and `y` downwards.
+///
+/// Points are ordered in reading order: top to bottom, then left to right.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Point {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Point {
+    pub const fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+
+    /// The adjacent point in direction `direction`, or `None` if that would
+    /// leave the first quadrant.
+    pub fn step(self, direction: Direction) -> Option<Point> {
+        Some(match direction {
+            Direction::North => Point::new(self.x, self.y.checked_sub(1)?),
+            Direction::East => Point::new(self.x.checked_add(1)?, self.y),
+            Direction::South => Point::new(self.x, self.y.checked_add(1)?),
+            Direction::West => Point::new(self.x.checked_sub(1)?, self.y),
+        })
+    }
+
+    pub fn manhattan_distance(self, other: Point) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+}
+
+impl PartialOrd for Point {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Point {
+    fn cmp(&self, other: &Self) -> Ordering {
+        usize::cmp(&self.y, &other.y).then_with(|| usize::cmp(&self.x, &other.x))
+    }
+}
+
+impl Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+/// A compass direction, where north is towards `y = 0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    /// Every direction, in the reading order of the neighbours they lead to.
+    pub const READING_ORDER: [Direction; 4] = [
+        Direction::North,
+        Direction::West,
+        Direction::East,
+        Direction::South,
+    ];
+
+    /// Turns 90° anticlockwise.
+    pub fn turn_left(self) -> Self {
+        match self {
+            Direction::North => Direction::West,
+            Direction::West => Direction::South,
+            Direction::South => Direction::East,
+            Direction::East => Direction::North,
+        }
+    }
+
+    /// Turns 90° clockwise.
+    pub fn turn_right(self) -> Self {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North,
+        }
+    }
+
+    pub fn reverse(self) -> Self {
+        self.turn_left().turn_left()
+    }
+}
+
+/// A rectangular grid of cells stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// A `width` by `height` grid with every cell set to `fill`.
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// A `width` by `height` grid with each cell set to `f` of its point.
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(Point) -> T) -> Self {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| Point::new(x, y)))
+            .map(&mut f)
+            .collect();
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Parses a map with one character per cell, such as
+    ///
+    /// ```text
+    /// #######
+    /// #.G.E.#
+    /// #######
+    /// ```
+    ///
+    /// where every line must be the same length.
+    pub fn parse(s: &str, mut f: impl FnMut(Point, char) -> Result<T>) -> Result<Self> {
+        let width = s
+            .lines()
+            .next()
+            .ok_or_else(|| eyre!("map is empty"))?
+            .chars()
+            .count();
+        let mut cells = Vec::with_capacity(s.len());
+        let mut height = 0;
+        for (y, line) in s.lines().enumerate() {
+            if line.chars().count() != width {
+                bail!(
+                    "Map must be rectangular, line {} is a different length to the first",
+                    y + 1
+                );
+            }
+            for (x, c) in line.chars().enumerate() {
+                cells.push(f(Point::new(x, y), c)?);
+            }
+            height += 1;
+        }
+        Ok(Self {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    /// Like [`Grid::parse`], but lines may have different lengths and short
+    /// lines are padded on the right with `fill`.
+    pub fn parse_ragged(
+        s: &str,
+        fill: T,
+        mut f: impl FnMut(Point, char) -> Result<T>,
+    ) -> Result<Self>
+    where
+        T: Clone,
+    {
+        let width = s
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut cells = Vec::with_capacity(s.len());
+        let mut height = 0;
+        for (y, line) in s.lines().enumerate() {
+            let mut len = 0;
+            for (x, c) in line.chars().enumerate() {
+                cells.push(f(Point::new(x, y), c)?);
+                len += 1;
+            }
+            cells.extend(std::iter::repeat_n(fill.clone(), width - len));
+            height += 1;
+        }
+        Ok(Self {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        point.x < self.width && point.y < self.height
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        if self.contains(point) {
+            Some(&self.cells[point.y * self.width + point.x])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        if self.contains(point) {
+            Some(&mut self.cells[point.y * self.width + point.x])
+        } else {
+            None
+        }
+    }
+
+    /// Every point in the grid, in reading order.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Point::new(x, y)))
+    }
+
+    /// Every cell with its point, in reading order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(&self.cells)
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Point, &mut T)> {
+        self.points().zip(&mut self.cells)
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on a chunk size of zero, but then there are no cells.
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// The cells on the edge of the grid, each exactly once.
+    pub fn border(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.iter().filter(move |(point, _)| {
+            point.x == 0 || point.y == 0 || point.x + 1 == self.width || point.y + 1 == self.height
+        })
+    }
+
+    /// The neighbour of `point` in `direction`, if it is in the grid.
+    pub fn neighbour(&self, point: Point, direction: Direction) -> Option<Point> {
+        point.step(direction).filter(|&point| self.contains(point))
+    }
+
+    /// The up to four orthogonal neighbours of `point` in the grid, in reading
+    /// order.
+    pub fn neighbours4(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction::READING_ORDER
+            .into_iter()
+            .filter_map(move |direction| self.neighbour(point, direction))
+    }
+
+    /// The up to eight orthogonal and diagonal neighbours of `point` in the
+    /// grid, in reading order.
+    pub fn neighbours8(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        const OFFSETS: [(isize, isize); 8] = [
+            (-1, -1),
+            (0, -1),
+            (1, -1),
+            (-1, 0),
+            (1, 0),
+            (-1, 1),
+            (0, 1),
+            (1, 1),
+        ];
+        OFFSETS.into_iter().filter_map(move |(dx, dy)| {
+            let neighbour = Point::new(
+                point.x.checked_add_signed(dx)?,
+                point.y.checked_add_signed(dy)?,
+            );
+            self.contains(neighbour).then_some(neighbour)
+        })
+    }
+
+    pub fn map<U>(&self, mut f: impl FnMut(Point, &T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.iter().map(|(point, cell)| f(point, cell)).collect(),
+        }
+    }
+
+    /// Displays the grid one row per line, drawing each cell with `f`.
+    pub fn display_with<'a, F>(&'a self, f: F) -> impl Display + 'a
+    where
+        F: Fn(Point, &T) -> char + 'a,
+    {
+        DisplayWith { grid: self, f }
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &Self::Output {
+        match self.get(point) {
+            Some(cell) => cell,
+            None => panic!(
+                "{} is out of bounds of grid of size ({}, {})",
+                point, self.width, self.height
+            ),
+        }
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut Self::Output {
+        let (width, height) = (self.width, self.height);
+        match self.get_mut(point) {
+            Some(cell) => cell,
+            None => panic!(
+                "{} is out of bounds of grid of size ({}, {})",
+                point, width, height
+            ),
+        }
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                Display::fmt(cell, f)?;
+            }
+            f.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+struct DisplayWith<'a, T, F> {
+    grid: &'a Grid<T>,
+    f: F,
+}
+
+impl<T, F: Fn(Point, &T) -> char> Display for DisplayWith<'_, T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (point, cell) in self.grid.iter() {
+            f.write_char((self.f)(point, cell))?;
+            if point.x + 1 == self.grid.width {
+                f.write_char('\n')?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reading_order() {
+        let mut points = vec![Point::new(1, 1), Point::new(2, 0), Point::new(0, 1)];
+        points.sort();
+        assert_eq!(
+            vec![Point::new(2, 0), Point::new(0, 1), Point::new(1, 1)],
+            points
+        );
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = Grid::new(3, 2, ());
+        assert_eq!(
+            vec![Point::new(1, 0), Point::new(0, 1)],
+            grid.neighbours4(Point::new(0, 0)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![Point::new(0, 0), Point::new(2, 0), Point::new(1, 1)],
+            grid.neighbours4(Point::new(1, 0)).collect::<Vec<_>>()
+        );
+        assert_eq!(5, grid.neighbours8(Point::new(1, 1)).count());
+        assert_eq!(3, grid.neighbours8(Point::new(2, 1)).count());
+    }
+
+    #[test]
+    fn rotations() {
+        for direction in Direction::READING_ORDER {
+            assert_eq!(direction, direction.turn_left().turn_right());
+            assert_eq!(direction.reverse(), direction.turn_right().turn_right());
+        }
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let map = "#.#\n..#\n";
+        let grid = Grid::parse(map, |_, c| Ok(c)).unwrap();
+        assert_eq!((3, 2), (grid.width(), grid.height()));
+        assert_eq!('#', grid[Point::new(2, 1)]);
+        assert_eq!(map, grid.to_string());
+        assert_eq!(
+            ".#.\n##.\n",
+            grid.display_with(|_, &c| if c == '#' { '.' } else { '#' })
+                .to_string()
+        );
+        assert!(Grid::parse("##\n#\n", |_, c| Ok(c)).is_err());
+    }
+
+    #[test]
+    fn parse_ragged() {
+        let grid = Grid::parse_ragged("ab\nc\n", ' ', |_, c| Ok(c)).unwrap();
+        assert_eq!("ab\nc \n", grid.to_string());
+    }
+}
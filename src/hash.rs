@@ -0,0 +1,92 @@
+//! Hash maps and sets for the solutions, which iterate in an order fixed by a
+//! seed instead of one that changes between runs.
+//!
+//! No answer should depend on iteration order: ties are always broken by an
+//! explicit rule. Tests check this by solving the examples and most bundled
+//! inputs under several seeds.
+
+use std::{cell::Cell, collections, hash::BuildHasher};
+
+use ahash::{AHasher, RandomState};
+
+pub type HashMap<K, V> = collections::HashMap<K, V, SeededState>;
+
+pub type HashSet<T> = collections::HashSet<T, SeededState>;
+
+thread_local! {
+    static SEED: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Changes the seed of every map and set created from now on by this thread,
+/// leaving other threads' unaffected.
+pub fn set_seed(seed: u64) {
+    SEED.with(|current| current.set(seed));
+}
+
+/// Builds the hashers for [`HashMap`] and [`HashSet`] from the current
+/// thread's seed.
+#[derive(Debug, Clone)]
+pub struct SeededState(RandomState);
+
+impl Default for SeededState {
+    fn default() -> Self {
+        let seed = SEED.with(Cell::get);
+        Self(RandomState::with_seeds(seed, seed, seed, seed))
+    }
+}
+
+impl BuildHasher for SeededState {
+    type Hasher = AHasher;
+
+    fn build_hasher(&self) -> AHasher {
+        self.0.build_hasher()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::Rng, Context, SOLUTIONS};
+
+    /// Days whose bundled input takes too long to solve several times over.
+    const SLOW: [usize; 1] = [11];
+
+    /// Every answer of the default variants on every example, and on the
+    /// bundled input of every day not in [`SLOW`].
+    fn answers() -> Vec<String> {
+        let mut answers = Vec::new();
+        for (day, solution) in (1..).zip(&SOLUTIONS) {
+            let bundled = Some(solution.input).filter(|_| !SLOW.contains(&day));
+            let inputs = bundled
+                .map(|input| (input, Default::default()))
+                .into_iter()
+                .chain(
+                    solution
+                        .examples
+                        .iter()
+                        .map(|example| (example.input, example.params())),
+                );
+            for (input, params) in inputs {
+                let ctx = Context::default().with_params(std::sync::Arc::new(params));
+                for variant in solution.parts().into_iter().filter_map(<[_]>::first) {
+                    answers.push(match (variant.run)(input, &ctx) {
+                        Ok(answer) => answer,
+                        Err(error) => format!("{:#}", error),
+                    });
+                }
+            }
+        }
+        answers
+    }
+
+    #[test]
+    fn answers_do_not_depend_on_seed() {
+        let expected = answers();
+        let mut rng = Rng::new(2018);
+        for _ in 0..3 {
+            set_seed(rng.next_u64());
+            assert_eq!(expected, answers());
+        }
+        set_seed(0);
+    }
+}
@@ -0,0 +1,240 @@
+//! Cleaning up puzzle inputs before they are parsed, so that every day
+//! accepts inputs however they were saved.
+//!
+//! Inputs streamed to a [`Stream`](crate::Stream) are cleaned up as they are
+//! read by [`chars`] or [`for_each_line`] instead.
+
+use std::{
+    borrow::Cow,
+    io::{self, BufRead},
+    str,
+};
+
+use eyre::Result;
+
+const BOM: char = '\u{feff}';
+
+/// Puts `input` in the form every parser expects: no byte order mark, `\n`
+/// line endings, no trailing whitespace on any line, and no blank lines at
+/// the end, which is followed by exactly one newline.
+///
+/// Leading whitespace is kept since it is significant in maps such as day
+/// 13's. Returns `input` unchanged if it is already in this form.
+///
+/// ```
+/// use aoc2018::input::normalize;
+///
+/// assert_eq!("a\n  b\n", normalize("\u{feff}a \r\n  b\r\n\r\n"));
+/// assert_eq!("a\n", normalize("a"));
+/// ```
+pub fn normalize(input: &str) -> Cow<'_, str> {
+    if is_normal(input) {
+        return Cow::Borrowed(input);
+    }
+    let mut normal = String::with_capacity(input.len() + 1);
+    for line in input.strip_prefix(BOM).unwrap_or(input).lines() {
+        normal.push_str(line.trim_end());
+        normal.push('\n');
+    }
+    normal.truncate(normal.trim_end().len());
+    if !normal.is_empty() {
+        normal.push('\n');
+    }
+    Cow::Owned(normal)
+}
+
+fn is_normal(input: &str) -> bool {
+    if input.is_empty() {
+        return true;
+    }
+    !input.starts_with(BOM)
+        && !input.contains('\r')
+        && input.ends_with('\n')
+        && !input[..input.len() - 1].ends_with(char::is_whitespace)
+        && input
+            .lines()
+            .all(|line| !line.ends_with(char::is_whitespace))
+}
+
+/// Calls `f` with each line of `reader` as [`normalize`] would leave it,
+/// reading one line at a time.
+///
+/// ```
+/// use aoc2018::input::for_each_line;
+///
+/// let mut lines = Vec::new();
+/// for_each_line(&b"\xef\xbb\xbf\na \r\n\n  b\r\n\r\n"[..], |line| {
+///     lines.push(line.to_string());
+///     Ok(())
+/// })
+/// .unwrap();
+/// assert_eq!(vec!["", "a", "", "  b"], lines);
+/// ```
+pub fn for_each_line(
+    mut reader: impl BufRead,
+    mut f: impl FnMut(&str) -> Result<()>,
+) -> Result<()> {
+    skip_bom(&mut reader)?;
+    let mut line = String::new();
+    // Blank lines are held back until a line follows them, since blank lines
+    // at the end are dropped.
+    let mut blank = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let trimmed = line.trim_end();
+        if trimmed.is_empty() {
+            blank += 1;
+            continue;
+        }
+        for _ in 0..blank {
+            f("")?;
+        }
+        blank = 0;
+        f(trimmed)?;
+    }
+}
+
+/// The characters of the UTF-8 text in `reader`, after any byte order mark.
+///
+/// Unlike [`normalize`] this keeps all whitespace, which suits parsers that
+/// skip it anyway.
+///
+/// ```
+/// use aoc2018::input::chars;
+///
+/// let text: String = chars(&b"\xef\xbb\xbfd\xc3\xa9j\xc3\xa0\r\n"[..])
+///     .unwrap()
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!("déjà\r\n", text);
+/// assert!(chars(&b"\xc3("[..]).unwrap().any(|c| c.is_err()));
+/// ```
+pub fn chars<R: BufRead>(mut reader: R) -> io::Result<Chars<R>> {
+    skip_bom(&mut reader)?;
+    Ok(Chars { reader })
+}
+
+/// An iterator over the characters of a stream, returned by [`chars`].
+#[derive(Debug)]
+pub struct Chars<R> {
+    reader: R,
+}
+
+impl<R: BufRead> Iterator for Chars<R> {
+    type Item = io::Result<char>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = match self.reader.fill_buf() {
+            Ok(buf) => *buf.first()?,
+            Err(error) => return Some(Err(error)),
+        };
+        if first.is_ascii() {
+            self.reader.consume(1);
+            return Some(Ok(first as char));
+        }
+        // Any other byte that does not start a sequence is invalid on its own.
+        let len = match first {
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => 1,
+        };
+        let mut bytes = [0; 4];
+        if let Err(error) = self.reader.read_exact(&mut bytes[..len]) {
+            return Some(Err(error));
+        }
+        Some(
+            str::from_utf8(&bytes[..len])
+                .map(|c| c.chars().next().unwrap())
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error)),
+        )
+    }
+}
+
+fn skip_bom(reader: &mut impl BufRead) -> io::Result<()> {
+    const BOM_BYTES: &[u8] = "\u{feff}".as_bytes();
+    if reader.fill_buf()?.starts_with(BOM_BYTES) {
+        reader.consume(BOM_BYTES.len());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{Context, SOLUTIONS};
+
+    #[test]
+    fn forms() {
+        assert_eq!("", normalize(""));
+        assert_eq!("", normalize(" \r\n\n"));
+        assert!(matches!(normalize("a\n\nb\n"), Cow::Borrowed("a\n\nb\n")));
+        assert_eq!("a\n\nb\n", normalize("a\r\n\r\nb"));
+        assert_eq!("#.\n #\n", normalize("#. \t\n #\n\n\n"));
+    }
+
+    /// The ways an input might be saved differently from the bundled one.
+    fn variations(input: &str) -> Vec<String> {
+        let trimmed = input.trim_end();
+        vec![
+            input.replace('\n', "\r\n"),
+            format!("{}{}", BOM, input),
+            input.replace('\n', "  \n"),
+            trimmed.to_string(),
+            format!("{}\n\n\n", trimmed),
+            format!("{}{}\r\n", BOM, trimmed.replace('\n', " \r\n")),
+        ]
+    }
+
+    /// Every variation of every day's bundled input normalizes to the same
+    /// text as the original.
+    #[test]
+    fn every_input_normalizes_variations() {
+        for (day, solution) in SOLUTIONS.iter().enumerate() {
+            let normal = normalize(solution.input);
+            for variation in variations(solution.input) {
+                assert_eq!(normal, normalize(&variation), "day {}", day + 1);
+            }
+        }
+    }
+
+    /// The default variants give the same answers on every variation of every
+    /// example, once normalized, as on the example itself.
+    #[test]
+    fn every_example_accepts_variations() {
+        for (day, solution) in SOLUTIONS.iter().enumerate() {
+            for example in solution.examples {
+                let ctx = Context::default().with_params(Arc::new(example.params()));
+                for variant in solution.parts().into_iter().filter_map(<[_]>::first) {
+                    let answer = (variant.run)(example.input, &ctx).unwrap();
+                    for variation in variations(example.input) {
+                        let varied = (variant.run)(&normalize(&variation), &ctx);
+                        assert_eq!(answer, varied.unwrap(), "day {}", day + 1);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Every streaming variant gives the same answers as its in-memory
+    /// implementation, however the input was saved.
+    #[test]
+    fn streams_agree() {
+        for (day, solution) in SOLUTIONS.iter().enumerate() {
+            let variants = solution.parts().into_iter().flatten();
+            for (variant, stream) in variants.filter_map(|v| Some((v, v.stream?))) {
+                let ctx = Context::default();
+                let answer = (variant.run)(solution.input, &ctx).unwrap();
+                for input in variations(solution.input) {
+                    let streamed = stream(&mut input.as_bytes(), &ctx);
+                    assert_eq!(answer, streamed.unwrap(), "day {}", day + 1);
+                }
+            }
+        }
+    }
+}
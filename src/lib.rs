@@ -1,55 +1,228 @@
-use eyre::Result;
-
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
+//! Solutions to Advent of Code 2018.
+//!
+//! Each `dayNN` module exposes the types and functions used to solve that day
+//! so that they can be reused, alongside a [`Solution`] that wires them up to
+//! the puzzle input.
+//!
+//! Every day is behind a cargo feature of the same name, all of which are
+//! enabled by the default `all-days` feature. Days that are not compiled in
+//! still have an entry in [`SOLUTIONS`], which fails when run.
+//!
+//! The default `cli` feature builds the `aoc2018` binary along with the
+//! [`config`] file and HTTP [`serve`]r it uses. Drawing images and playing
+//! animations are behind the `render` and `animate` features, which `cli`
+//! enables. Embedders that only want some days can turn off the default
+//! features and skip those dependencies.
+
+use std::io::BufRead;
+
+#[cfg(feature = "animate")]
+use animate::Animate;
+use eyre::{bail, Result};
+#[cfg(feature = "render")]
+use render::Render;
+
+pub use context::{CancelReason, CancelToken, Cancelled, Context, Progress};
+pub use explain::{Explain, Fact, Inspect};
+pub use param::{Example, Param, Params};
+
+#[cfg(feature = "animate")]
+pub mod animate;
+pub mod cache;
+#[cfg(feature = "cli")]
+pub mod config;
+mod context;
+pub mod cycle;
+#[cfg(feature = "day01")]
+pub mod day01;
+#[cfg(feature = "day02")]
+pub mod day02;
+#[cfg(feature = "day03")]
+pub mod day03;
+#[cfg(feature = "day04")]
+pub mod day04;
+#[cfg(feature = "day05")]
+pub mod day05;
+#[cfg(feature = "day06")]
+pub mod day06;
+#[cfg(feature = "day07")]
+pub mod day07;
+#[cfg(feature = "day08")]
+pub mod day08;
+#[cfg(feature = "day09")]
+pub mod day09;
+#[cfg(feature = "day10")]
+pub mod day10;
+#[cfg(feature = "day11")]
+pub mod day11;
+#[cfg(feature = "day12")]
+pub mod day12;
+#[cfg(feature = "day13")]
+pub mod day13;
+#[cfg(feature = "day14")]
+pub mod day14;
+#[cfg(feature = "day15")]
+pub mod day15;
+#[cfg(feature = "day16")]
+pub mod day16;
+mod explain;
+pub mod ffi;
+pub mod grid;
+pub mod hash;
+pub mod input;
+pub mod metrics;
+mod param;
+pub mod parse;
+#[cfg(feature = "render")]
+pub mod render;
+pub mod search;
+#[cfg(feature = "cli")]
+pub mod serve;
+#[cfg(test)]
+mod testing;
 
 pub struct Solution {
     pub input: &'static str,
-    pub part1: fn(&str) -> Result<String>,
-    pub part2: fn(&str) -> Result<String>,
+    /// Implementations of part 1, the first of which is the default.
+    pub part1: &'static [Variant],
+    /// Implementations of part 2, the first of which is the default.
+    pub part2: &'static [Variant],
+    /// Draws the input as an image, for days with spatial data.
+    #[cfg(feature = "render")]
+    pub render: Option<Render>,
+    /// Shows how the puzzle plays out, for days that simulate something.
+    #[cfg(feature = "animate")]
+    pub animate: Option<Animate>,
+    /// Summarizes the shape of an input.
+    pub inspect: Option<Inspect>,
+    /// The puzzle constants the solution reads from its [`Context`].
+    pub params: &'static [Param],
+    pub examples: &'static [Example],
+}
+
+impl Solution {
+    /// The variants of both parts, in part order.
+    pub fn parts(&self) -> [&'static [Variant]; 2] {
+        [self.part1, self.part2]
+    }
+
+    /// Whether the day has been solved and compiled in, unlike [`NOT_COMPILED`]
+    /// and the days still to do, which have no bundled input.
+    pub fn is_solved(&self) -> bool {
+        !self.input.is_empty()
+    }
+
+    /// Checks that `params` only overrides this day's parameters, with valid
+    /// values.
+    pub fn check_params(&self, params: &Params) -> Result<()> {
+        param::check_params(self.params, params)
+    }
+}
+
+/// A named implementation of one part of a day.
+#[derive(Debug, Clone, Copy)]
+pub struct Variant {
+    pub name: &'static str,
+    pub run: fn(&str, &Context) -> Result<String>,
+    /// Solves the part reading the input as it goes, for inputs too large to
+    /// hold in memory. Gives the same answers as `run`.
+    pub stream: Option<Stream>,
+}
+
+/// A part's solution that reads its input from a stream, which is not
+/// normalized beyond what [`input::chars`] and [`input::for_each_line`] do.
+pub type Stream = fn(&mut dyn BufRead, &Context) -> Result<String>;
+
+impl Variant {
+    pub const fn new(name: &'static str, run: fn(&str, &Context) -> Result<String>) -> Self {
+        Self {
+            name,
+            run,
+            stream: None,
+        }
+    }
+
+    /// Adds a streaming implementation of this variant.
+    pub const fn with_stream(mut self, stream: Stream) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+}
+
+/// Looks up a variant by name, or the default variant if `name` is `None`.
+pub fn find_variant(variants: &[Variant], name: Option<&str>) -> Option<Variant> {
+    match name {
+        Some(name) => variants
+            .iter()
+            .find(|variant| variant.name == name)
+            .copied(),
+        None => variants.first().copied(),
+    }
 }
 
 const UNSOLVED: Solution = Solution {
     input: "",
-    part1: unsolved,
-    part2: unsolved,
+    part1: &[Variant::new("unsolved", unsolved)],
+    part2: &[Variant::new("unsolved", unsolved)],
+    #[cfg(feature = "render")]
+    render: None,
+    #[cfg(feature = "animate")]
+    animate: None,
+    inspect: None,
+    params: &[],
+    examples: &[],
 };
 
-fn unsolved(_: &str) -> Result<String> {
+fn unsolved(_: &str, _: &Context) -> Result<String> {
     Ok("unsolved".to_string())
 }
 
+/// The entry for a day whose cargo feature is disabled.
+pub const NOT_COMPILED: Solution = Solution {
+    input: "",
+    part1: &[Variant::new("not-compiled", not_compiled)],
+    part2: &[Variant::new("not-compiled", not_compiled)],
+    #[cfg(feature = "render")]
+    render: None,
+    #[cfg(feature = "animate")]
+    animate: None,
+    inspect: None,
+    params: &[],
+    examples: &[],
+};
+
+fn not_compiled(_: &str, _: &Context) -> Result<String> {
+    bail!("This day was not compiled in, rebuild with its `dayNN` cargo feature enabled")
+}
+
+/// A day's [`Solution`] if its feature is enabled, otherwise [`NOT_COMPILED`].
+macro_rules! solution {
+    ($day:ident, $feature:literal) => {{
+        #[cfg(feature = $feature)]
+        let solution = $day::SOLUTION;
+        #[cfg(not(feature = $feature))]
+        let solution = NOT_COMPILED;
+        solution
+    }};
+}
+
 pub const SOLUTIONS: [Solution; 25] = [
-    day01::SOLUTION,
-    day02::SOLUTION,
-    day03::SOLUTION,
-    day04::SOLUTION,
-    day05::SOLUTION,
-    day06::SOLUTION,
-    day07::SOLUTION,
-    day08::SOLUTION,
-    day09::SOLUTION,
-    day10::SOLUTION,
-    day11::SOLUTION,
-    day12::SOLUTION,
-    day13::SOLUTION,
-    day14::SOLUTION,
-    day15::SOLUTION,
-    day16::SOLUTION,
+    solution!(day01, "day01"),
+    solution!(day02, "day02"),
+    solution!(day03, "day03"),
+    solution!(day04, "day04"),
+    solution!(day05, "day05"),
+    solution!(day06, "day06"),
+    solution!(day07, "day07"),
+    solution!(day08, "day08"),
+    solution!(day09, "day09"),
+    solution!(day10, "day10"),
+    solution!(day11, "day11"),
+    solution!(day12, "day12"),
+    solution!(day13, "day13"),
+    solution!(day14, "day14"),
+    solution!(day15, "day15"),
+    solution!(day16, "day16"),
     UNSOLVED,
     UNSOLVED,
     UNSOLVED,
@@ -1,36 +1,929 @@
-use std::{fs, path::PathBuf};
+use std::{
+    borrow::Cow,
+    fs::{self, File},
+    io::{self, BufReader, IsTerminal},
+    net::IpAddr,
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
 
-use aoc2018::SOLUTIONS;
-use clap::Parser;
+use aoc2018::{
+    animate,
+    cache::{self, Cache},
+    config::{self, Config},
+    find_variant, input,
+    metrics::Registry,
+    render,
+    serve::{self, Server},
+    CancelReason, CancelToken, Cancelled, Context, Explain, Fact, Params, Progress, Solution,
+    Variant, SOLUTIONS,
+};
+use clap::{ArgEnum, Parser, Subcommand};
 use color_eyre::Result;
-use eyre::bail;
+use eyre::{bail, eyre, WrapErr};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use itertools::Itertools;
+use serde::Serialize;
 use tracing_error::ErrorLayer;
 use tracing_subscriber::{prelude::*, EnvFilter};
 
 #[derive(Debug, Parser)]
-struct Args {
+#[clap(
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true,
+    after_help = SETTINGS_HELP
+)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Option<Command>,
+    #[clap(flatten)]
+    run: RunArgs,
+}
+
+const SETTINGS_HELP: &str = "\
+SETTINGS:
+    Settings are read from $AOC2018_CONFIG, or else aoc2018.toml in the current
+    directory, which can set `input_dir`, `format`, `log_level` and a [dayNN]
+    table of parameters for each day. Command line flags take precedence over
+    the environment variables $AOC2018_INPUT_DIR, $AOC2018_FORMAT and
+    $RUST_LOG, which take precedence over the file. Parameters given with -P
+    take precedence over those of an --example, which take precedence over
+    the file.";
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Watch a day's simulation play out in the terminal.
+    ///
+    /// Space pauses, the arrow keys step backwards and forwards a frame or
+    /// change the speed, `r` restarts and `q` quits.
+    Animate(AnimateArgs),
+    /// Parse a day's input and summarize it, without solving it.
+    ///
+    /// Fails with the parser's error if the input is malformed.
+    Inspect(InspectArgs),
+    /// Answer HTTP requests to solve days, for running the solutions without
+    /// a Rust toolchain.
+    ///
+    /// `GET /days` lists the solved days, and `POST /day/{n}/part/{p}` solves
+    /// the input in the request body. Parameters may be overridden with query
+    /// arguments, e.g. `?workers=2`.
+    Serve(ServeArgs),
+    /// Manage the cache of previous answers.
+    Cache {
+        #[clap(subcommand)]
+        command: CacheCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum CacheCommand {
+    /// Delete every cached answer.
+    Clear,
+}
+
+/// Solves a day, which is what happens without a subcommand.
+#[derive(Debug, clap::Args)]
+struct RunArgs {
+    #[clap(required = true)]
+    day: Option<usize>,
+    /// The puzzle input, by default `dayNN.txt` in the configured input
+    /// directory or else the bundled input.
+    input: Option<PathBuf>,
+    /// Solve the Nth example from the puzzle text, with the parameters it was
+    /// worked with, instead of an input.
+    #[clap(long, value_name = "N", conflicts_with_all = &["input", "inputs"])]
+    example: Option<usize>,
+    /// Override one of the day's parameters, such as `-P workers=2`.
+    ///
+    /// An unknown name lists the day's parameters and their defaults.
+    #[clap(
+        short = 'P',
+        long = "param",
+        value_name = "NAME=VALUE",
+        parse(try_from_str = parse_param)
+    )]
+    params: Vec<(String, String)>,
+    /// Run every file in this directory instead of a single input.
+    ///
+    /// Answers are checked against a sibling `<name>.answers` file if there is
+    /// one, which should contain the output of a known-good run.
+    #[clap(long, conflicts_with = "input")]
+    inputs: Option<PathBuf>,
+    /// Run the named implementation of each part instead of the default.
+    ///
+    /// Parts without a variant of that name run their default.
+    #[clap(long)]
+    variant: Option<String>,
+    /// Give up on a part once it has run for this long, e.g. `10s` or `2m`.
+    #[clap(long, parse(try_from_str = humantime::parse_duration))]
+    timeout: Option<Duration>,
+    /// Draw the input to this `.png` or `.ppm` file instead of solving it.
+    ///
+    /// Only days with spatial data, such as maps or grids, support this.
+    #[clap(long, conflicts_with = "inputs")]
+    render: Option<PathBuf>,
+    /// Solve every part, ignoring and not updating the cache of answers.
+    ///
+    /// Answers are cached by day, part, variant, input and build, in
+    /// `$AOC2018_CACHE_DIR` or else the user's cache directory.
+    #[clap(long)]
+    no_cache: bool,
+    /// Also show the intermediate results each part reports about how it
+    /// reached its answer.
+    #[clap(long)]
+    explain: bool,
+    /// Also show how much work each part did, such as how many nodes its
+    /// searches expanded.
+    #[clap(long)]
+    metrics: bool,
+    /// Stream input files larger than this many bytes to the parts that can
+    /// solve them without reading them into memory.
+    ///
+    /// Streamed answers are not cached.
+    #[clap(long, value_name = "BYTES", default_value_t = 64 << 20)]
+    stream_above: u64,
+    /// Print answers as plain text or as a JSON document [default: text]
+    #[clap(long, arg_enum)]
+    format: Option<Format>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Debug, clap::Args)]
+struct InspectArgs {
     day: usize,
     input: Option<PathBuf>,
+    /// Print the summary as plain text or as a JSON document [default: text]
+    #[clap(long, arg_enum)]
+    format: Option<Format>,
+}
+
+#[derive(Debug, clap::Args)]
+struct ServeArgs {
+    #[clap(long, default_value_t = 8018)]
+    port: u16,
+    /// The address to listen on. Only this machine can connect to the default.
+    #[clap(long, default_value = "127.0.0.1")]
+    host: IpAddr,
+    /// Give up on a part once it has run for this long, e.g. `10s` or `2m`
+    /// [default: 1m]
+    #[clap(long, parse(try_from_str = humantime::parse_duration))]
+    timeout: Option<Duration>,
+}
+
+#[derive(Debug, clap::Args)]
+struct AnimateArgs {
+    day: usize,
+    input: Option<PathBuf>,
+    /// Frames shown per second when playing.
+    #[clap(long, default_value_t = 10.0)]
+    fps: f64,
+    /// Write each frame to a numbered file in this directory instead of
+    /// playing them.
+    #[clap(long)]
+    headless: Option<PathBuf>,
+    /// Stop after this many frames.
+    #[clap(long)]
+    max_frames: Option<usize>,
 }
 
 fn main() -> Result<()> {
+    color_eyre::install()?;
+    // A broken config file is reported once logging is set up, but its log
+    // level is needed before then.
+    let config = Config::load();
+    let log_level = config
+        .as_ref()
+        .ok()
+        .and_then(|config| config.log_level.as_deref())
+        .unwrap_or("info");
     tracing_subscriber::registry()
-        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(log_level)))
         .with(tracing_subscriber::fmt::layer().pretty())
         .with(ErrorLayer::default())
         .init();
-    color_eyre::install()?;
-    let args = Args::parse();
+    let config = config?;
+    let cli = Cli::parse();
+    match cli.command {
+        Some(Command::Animate(args)) => animate(args, &config),
+        Some(Command::Inspect(args)) => inspect(args, &config),
+        Some(Command::Serve(args)) => {
+            let server = Server::bind(
+                (args.host, args.port),
+                args.timeout.unwrap_or(serve::DEFAULT_TIMEOUT),
+            )
+            .wrap_err_with(|| format!("Could not listen on {}:{}", args.host, args.port))?;
+            tracing::info!("Listening on http://{}", server.local_addr()?);
+            Ok(server.run()?)
+        }
+        Some(Command::Cache {
+            command: CacheCommand::Clear,
+        }) => {
+            let cache = Cache::default_location().ok_or_else(|| {
+                eyre!("Could not find a cache directory, set ${}", cache::DIR_VAR)
+            })?;
+            cache.clear()?;
+            println!("Cleared {}", cache.dir().display());
+            Ok(())
+        }
+        None => run(cli.run, &config),
+    }
+}
+
+fn run(args: RunArgs, config: &Config) -> Result<()> {
+    let day = args.day.expect("clap requires a day without a subcommand");
+    handle_interrupts()?;
+    let solution = solution(day)?;
+    let format = format(args.format, config)?;
+    let example = match args.example {
+        Some(_) if solution.examples.is_empty() => bail!("Day {} has no examples", day),
+        Some(n) => Some(solution.examples.get(n.wrapping_sub(1)).ok_or_else(|| {
+            eyre!(
+                "Day {} has no example {}, the examples are numbered 1 to {}",
+                day,
+                n,
+                solution.examples.len()
+            )
+        })?),
+        None => None,
+    };
+    let mut params = config.params(day);
+    if let Some(example) = example {
+        params.extend(example.params());
+    }
+    params.extend(args.params.iter().cloned());
+    solution
+        .check_params(&params)
+        .wrap_err_with(|| format!("Invalid parameters for day {}", day))?;
+    if let Some(name) = &args.variant {
+        if solution
+            .parts()
+            .iter()
+            .all(|variants| find_variant(variants, Some(name)).is_none())
+        {
+            let available: Vec<_> = solution
+                .parts()
+                .iter()
+                .flat_map(|variants| variants.iter().map(|variant| variant.name))
+                .unique()
+                .collect();
+            bail!(
+                "Day {} has no variant {:?}, available variants are: {}",
+                day,
+                name,
+                available.join(", ")
+            );
+        }
+    }
+    let variants = solution
+        .parts()
+        .into_iter()
+        .enumerate()
+        .map(|(part, variants)| {
+            find_variant(variants, args.variant.as_deref())
+                .or_else(|| find_variant(variants, None))
+                .ok_or_else(|| eyre!("Part {} has no implementations", part + 1))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let cache = if args.no_cache {
+        None
+    } else {
+        Cache::default_location().map(|cache| (cache, cache::build_id()))
+    };
+    let runner = Runner {
+        day,
+        variants,
+        timeout: args.timeout,
+        format,
+        cache,
+        explain: args.explain,
+        metrics: args.metrics,
+        params: Arc::new(params),
+    };
+
+    if let Some(dir) = &args.inputs {
+        if !run_batch(&runner, dir, format)? {
+            process::exit(exit_code());
+        }
+        return Ok(());
+    }
 
-    if args.day > SOLUTIONS.len() {
+    let path = args.input.or_else(|| config.input_path(day));
+    let streamed = match &path {
+        Some(path) if example.is_none() && args.render.is_none() => {
+            let len = fs::metadata(path)
+                .wrap_err_with(|| format!("Could not read {}", path.display()))?
+                .len();
+            (len > args.stream_above).then_some(path.as_path())
+        }
+        _ => None,
+    };
+    // Only read the input into memory once a part needs it.
+    let mut text = None;
+    let mut input = || -> Result<&'static str> {
+        if let Some(text) = text {
+            return Ok(text);
+        }
+        let input = match example {
+            Some(example) => normalize(example.input),
+            None => read_input(path.clone(), solution)?,
+        };
+        Ok(*text.insert(input))
+    };
+    if let Some(path) = &args.render {
+        let input = input()?;
+        let render = solution
+            .render
+            .ok_or_else(|| eyre!("Day {} does not support --render", day))?;
+        let ctx = Context::default().with_params(runner.params.clone());
+        let image = match with_context(args.timeout, format, ctx, |ctx| render(input, ctx)) {
+            Ok(image) => image,
+            Err(error) => match describe(&error, args.timeout) {
+                Some(description) => {
+                    eprintln!("Rendering {}", description);
+                    process::exit(exit_code());
+                }
+                None => return Err(error),
+            },
+        };
+        let longest_side = image.width().max(image.height()).max(1);
+        let image = render::upscale(&image, MIN_RENDER_SIZE / longest_side);
+        render::save(&image, path)?;
+        return Ok(());
+    }
+    let mut reports = Vec::new();
+    for part in 0..runner.variants.len() {
+        let result = match (streamed, runner.variants[part].stream) {
+            (Some(path), Some(_)) => runner.run_part(part, Input::Stream(path)),
+            _ => input().and_then(|input| runner.run_part(part, Input::Text(input))),
+        };
+        match format {
+            Format::Text => match result {
+                Ok(solved) => {
+                    println!("Part {}: {}", part + 1, solved.answer);
+                    print_facts(&solved.facts, "  ");
+                    print_metrics(&solved.metrics, "  ");
+                }
+                Err(error) => match describe(&error, args.timeout) {
+                    Some(description) => {
+                        eprintln!("Part {} {}", part + 1, description);
+                        process::exit(exit_code());
+                    }
+                    None => return Err(error),
+                },
+            },
+            Format::Json => reports.push(runner.report(part, result)),
+        }
+        if interrupted() {
+            break;
+        }
+    }
+    if format == Format::Json {
+        let failed = reports.iter().any(|report| report.error.is_some());
+        let report = serde_json::json!({ "day": day, "parts": reports });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        if failed {
+            process::exit(exit_code());
+        }
+    }
+    Ok(())
+}
+
+/// The output format from the command line, or else the config.
+fn format(flag: Option<Format>, config: &Config) -> Result<Format> {
+    Ok(match (flag, &config.format) {
+        (Some(format), _) => format,
+        (None, Some(name)) => Format::from_str(name, true).map_err(|_| {
+            eyre!(
+                "Invalid format {:?} in ${} or {}, expected text or json",
+                name,
+                config::FORMAT_VAR,
+                config::FILE_NAME
+            )
+        })?,
+        (None, None) => Format::Text,
+    })
+}
+
+fn inspect(args: InspectArgs, config: &Config) -> Result<()> {
+    let format = format(args.format, config)?;
+    let solution = solution(args.day)?;
+    let inspect = solution
+        .inspect
+        .ok_or_else(|| eyre!("Day {} cannot be inspected", args.day))?;
+    let input = read_input(args.input.or_else(|| config.input_path(args.day)), solution)?;
+    let facts = inspect(input)?;
+    match format {
+        Format::Text => print_facts(&facts, ""),
+        Format::Json => {
+            let report = serde_json::json!({ "day": args.day, "summary": Fact::table(facts) });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+    }
+    Ok(())
+}
+
+fn animate(args: AnimateArgs, config: &Config) -> Result<()> {
+    let solution = solution(args.day)?;
+    let produce_frames = solution
+        .animate
+        .ok_or_else(|| eyre!("Day {} cannot be animated", args.day))?;
+    let input = read_input(args.input.or_else(|| config.input_path(args.day)), solution)?;
+    let mut frames = produce_frames(input)?;
+    if let Some(max_frames) = args.max_frames {
+        frames = Box::new(frames.take(max_frames));
+    }
+    match args.headless {
+        Some(dir) => {
+            let count = animate::write_frames(frames, &dir)?;
+            println!("Wrote {} frames to {}", count, dir.display());
+            Ok(())
+        }
+        None => animate::play(frames, args.fps),
+    }
+}
+
+fn solution(day: usize) -> Result<&'static Solution> {
+    if day == 0 || day > SOLUTIONS.len() {
         bail!("Only solutions for days 1-{} exist", SOLUTIONS.len());
     }
-    let solution = &SOLUTIONS[args.day - 1];
-    let input = match args.input {
+    Ok(&SOLUTIONS[day - 1])
+}
+
+/// Parses a `-P name=value` parameter override.
+fn parse_param(param: &str) -> Result<(String, String)> {
+    let (name, value) = param
+        .split_once('=')
+        .ok_or_else(|| eyre!("Expected NAME=VALUE"))?;
+    Ok((name.trim().to_string(), value.trim().to_string()))
+}
+
+/// Reads the input at `path`, or the solution's bundled input if there is no
+/// path, normalized for the parsers.
+fn read_input(path: Option<PathBuf>, solution: &Solution) -> Result<&'static str> {
+    Ok(normalize(match path {
         Some(path) => Box::leak(fs::read_to_string(path)?.into_boxed_str()),
         None => solution.input,
+    }))
+}
+
+/// See [`input::normalize`].
+fn normalize(input: &'static str) -> &'static str {
+    match input::normalize(input) {
+        Cow::Borrowed(input) => input,
+        Cow::Owned(input) => Box::leak(input.into_boxed_str()),
+    }
+}
+
+/// Rendered images are scaled up by a whole factor to be at least this many
+/// pixels along their longest side, so that small maps are still visible.
+const MIN_RENDER_SIZE: usize = 600;
+
+/// Runs the chosen variant of each part of one day.
+struct Runner {
+    day: usize,
+    /// The variant to run for each part.
+    variants: Vec<Variant>,
+    timeout: Option<Duration>,
+    /// How answers are printed, which decides whether progress is drawn.
+    format: Format,
+    /// Where to look up and store answers, and the build they belong to.
+    cache: Option<(Cache, u64)>,
+    /// Whether to collect the facts each part reports.
+    explain: bool,
+    /// Whether to collect the metrics each part reports.
+    metrics: bool,
+    /// Overrides for the day's parameters.
+    params: Arc<Params>,
+}
+
+/// The answer to one part.
+struct Solved {
+    answer: String,
+    /// What the part reported about how it got there, if asked to explain.
+    facts: Vec<(&'static str, Fact)>,
+    /// How much work the part did, if asked for metrics.
+    metrics: Vec<(&'static str, Fact)>,
+    cached: bool,
+}
+
+/// What a part is solved for.
+#[derive(Debug, Clone, Copy)]
+enum Input<'a> {
+    /// A whole normalized input.
+    Text(&'a str),
+    /// A file to stream to the part's [`Variant::stream`], which it must have.
+    Stream(&'a Path),
+}
+
+/// One part's result, as printed by `--format json`.
+#[derive(Debug, Serialize)]
+struct PartReport {
+    part: usize,
+    variant: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    answer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    cached: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    facts: Option<Fact>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metrics: Option<Fact>,
+    /// The answer from an `.answers` file, when running a directory of inputs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expected: Option<String>,
+}
+
+impl Runner {
+    /// Solves `part` (counting from 0) of `input`, or returns the cached
+    /// answer if this build has solved it before.
+    ///
+    /// The cache is not read when explaining or measuring, since it keeps
+    /// neither facts nor metrics, nor when streaming, since hashing the input
+    /// would mean reading it twice.
+    fn run_part(&self, part: usize, input: Input) -> Result<Solved> {
+        let variant = self.variants[part];
+        let key = match input {
+            Input::Text(input) => self.cache.as_ref().map(|(cache, build)| {
+                let key = cache::Key {
+                    day: self.day,
+                    part: part + 1,
+                    variant: variant.name,
+                    input: cache::hash(input.as_bytes()),
+                    params: cache::hash_params(&self.params),
+                    build: *build,
+                };
+                (cache, key)
+            }),
+            Input::Stream(_) => None,
+        };
+        if let Some(answer) = key
+            .as_ref()
+            .filter(|_| !self.explain && !self.metrics)
+            .and_then(|(cache, key)| cache.get(key))
+        {
+            return Ok(Solved {
+                answer,
+                facts: Vec::new(),
+                metrics: Vec::new(),
+                cached: true,
+            });
+        }
+        let mut ctx = Context::default().with_params(self.params.clone());
+        let facts = self.explain.then(|| Arc::new(Facts::default()));
+        if let Some(facts) = &facts {
+            ctx = ctx.with_explain(facts.clone());
+        }
+        let metrics = self.metrics.then(|| Arc::new(Registry::default()));
+        if let Some(metrics) = &metrics {
+            ctx = ctx.with_metrics(metrics.clone());
+        }
+        let answer = with_context(self.timeout, self.format, ctx, |ctx| match input {
+            Input::Text(input) => (variant.run)(input, ctx),
+            Input::Stream(path) => {
+                let stream = variant.stream.expect("only streamable parts are streamed");
+                let file = File::open(path)
+                    .wrap_err_with(|| format!("Could not open {}", path.display()))?;
+                tracing::debug!(path = %path.display(), "Streaming input");
+                stream(&mut BufReader::new(file), ctx)
+            }
+        })?;
+        if let Some((cache, key)) = key {
+            // Failing to cache an answer should not lose it.
+            if let Err(error) = cache.put(&key, &answer) {
+                tracing::warn!(%error, "Could not cache answer");
+            }
+        }
+        let facts = match facts {
+            Some(facts) => facts.0.lock().unwrap().drain(..).collect(),
+            None => Vec::new(),
+        };
+        let metrics = match metrics {
+            Some(metrics) => metrics.facts(),
+            None => Vec::new(),
+        };
+        Ok(Solved {
+            answer,
+            facts,
+            metrics,
+            cached: false,
+        })
+    }
+
+    fn report(&self, part: usize, result: Result<Solved>) -> PartReport {
+        let (answer, error, cached, facts, metrics) = match result {
+            Ok(solved) => {
+                let facts = (!solved.facts.is_empty()).then(|| Fact::table(solved.facts));
+                let metrics = (!solved.metrics.is_empty()).then(|| Fact::table(solved.metrics));
+                (Some(solved.answer), None, solved.cached, facts, metrics)
+            }
+            Err(error) => {
+                let error =
+                    describe(&error, self.timeout).unwrap_or_else(|| format!("{:#}", error));
+                (None, Some(error), false, None, None)
+            }
+        };
+        PartReport {
+            part: part + 1,
+            variant: self.variants[part].name,
+            answer,
+            error,
+            cached,
+            facts,
+            metrics,
+            expected: None,
+        }
+    }
+}
+
+/// Collects the facts reported by one part.
+#[derive(Default)]
+struct Facts(Mutex<Vec<(&'static str, Fact)>>);
+
+impl Explain for Facts {
+    fn fact(&self, name: &'static str, value: Fact) {
+        self.0.lock().unwrap().push((name, value));
+    }
+}
+
+/// Prints one fact per line, putting each item of a list on its own line.
+fn print_facts(facts: &[(&'static str, Fact)], indent: &str) {
+    for (name, value) in facts {
+        match value {
+            Fact::List(items) => {
+                println!("{}{}:", indent, name);
+                for item in items {
+                    println!("{}  {}", indent, item);
+                }
+            }
+            value => println!("{}{}: {}", indent, name, value),
+        }
+    }
+}
+
+/// Prints metrics after a part's facts, under their own heading.
+fn print_metrics(metrics: &[(&'static str, Fact)], indent: &str) {
+    if !metrics.is_empty() {
+        println!("{}metrics:", indent);
+        print_facts(metrics, &format!("{}  ", indent));
+    }
+}
+
+/// Runs `f` with `ctx`, cancelling it after `timeout` and, if printing text
+/// and stderr is a terminal, drawing progress bars.
+fn with_context<T>(
+    timeout: Option<Duration>,
+    format: Format,
+    mut ctx: Context,
+    f: impl FnOnce(&Context) -> Result<T>,
+) -> Result<T> {
+    if let Some(timeout) = timeout {
+        ctx.cancel_token().cancel_after(timeout);
+    }
+    *RUNNING.lock().unwrap() = Some(ctx.cancel_token().clone());
+    if interrupted() {
+        ctx.cancel_token().cancel(CancelReason::Interrupted);
+    }
+    let bars =
+        (format == Format::Text && io::stderr().is_terminal()).then(|| Arc::new(Bars::default()));
+    if let Some(bars) = &bars {
+        ctx = ctx.with_progress(bars.clone());
+    }
+    let result = f(&ctx);
+    *RUNNING.lock().unwrap() = None;
+    if let Some(bars) = bars {
+        bars.clear();
+    }
+    result
+}
+
+/// Runs every input in `dir`, checking the answers against any sibling
+/// `.answers` file, and returns whether every part succeeded and matched.
+fn run_batch(runner: &Runner, dir: &Path, format: Format) -> Result<bool> {
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?;
+    paths.retain(|path| path.is_file() && path.extension() != Some(ANSWERS_EXTENSION.as_ref()));
+    paths.sort();
+
+    let mut all_ok = true;
+    let mut inputs = Vec::new();
+    for path in paths {
+        if format == Format::Text {
+            println!("{}:", path.display());
+        }
+        let input = input::normalize(&fs::read_to_string(&path)?).into_owned();
+        let answers_path = path.with_extension(ANSWERS_EXTENSION);
+        let expected = if answers_path.exists() {
+            Some(parse_answers(&fs::read_to_string(&answers_path)?))
+        } else {
+            None
+        };
+        let mut reports = Vec::new();
+        for part in 0..runner.variants.len() {
+            let result = runner.run_part(part, Input::Text(&input));
+            let (facts, metrics, cancelled) = match &result {
+                Ok(solved) => (solved.facts.clone(), solved.metrics.clone(), false),
+                Err(error) => (Vec::new(), Vec::new(), error.is::<Cancelled>()),
+            };
+            let mut report = runner.report(part, result);
+            report.expected = expected
+                .as_ref()
+                .and_then(|expected| expected.get(part))
+                .cloned();
+            let verdict = match (&report.answer, &report.expected) {
+                (Some(_), None) => String::new(),
+                (Some(answer), Some(expected)) if expected == answer.trim_end() => {
+                    " (ok)".to_string()
+                }
+                (Some(_), Some(expected)) => {
+                    all_ok = false;
+                    format!(" (MISMATCH, expected {})", expected)
+                }
+                (None, _) => {
+                    all_ok = false;
+                    String::new()
+                }
+            };
+            if format == Format::Text {
+                match (&report.answer, &report.error) {
+                    (Some(answer), _) => {
+                        println!("  Part {}: {}{}", part + 1, answer.trim_end(), verdict);
+                        print_facts(&facts, "    ");
+                        print_metrics(&metrics, "    ");
+                    }
+                    (None, Some(error)) if cancelled => {
+                        println!("  Part {} {}", part + 1, error)
+                    }
+                    (None, error) => {
+                        println!(
+                            "  Part {} failed: {}",
+                            part + 1,
+                            error.as_deref().unwrap_or("")
+                        )
+                    }
+                }
+            }
+            reports.push(report);
+            if interrupted() {
+                break;
+            }
+        }
+        inputs.push(serde_json::json!({ "path": path, "parts": reports }));
+        if interrupted() {
+            break;
+        }
+    }
+    if format == Format::Json {
+        let report = serde_json::json!({ "day": runner.day, "inputs": inputs, "ok": all_ok });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    }
+    Ok(all_ok)
+}
+
+const ANSWERS_EXTENSION: &str = "answers";
+
+/// Parses an `.answers` file, which uses the same `Part N: answer` layout as
+/// this binary's output so that a known-good run can be saved verbatim.
+///
+/// Lines that do not start a new part continue the previous answer, which
+/// allows for multi-line answers such as day 10's message.
+fn parse_answers(answers: &str) -> Vec<String> {
+    let mut parsed: Vec<String> = Vec::new();
+    for line in answers.lines() {
+        let next_part = format!("Part {}:", parsed.len() + 1);
+        match (line.strip_prefix(&next_part), parsed.last_mut()) {
+            (Some(answer), _) => {
+                parsed.push(answer.strip_prefix(' ').unwrap_or(answer).to_string())
+            }
+            (None, Some(answer)) => {
+                answer.push('\n');
+                answer.push_str(line);
+            }
+            (None, None) => {}
+        }
+    }
+    for answer in &mut parsed {
+        answer.truncate(answer.trim_end().len());
+    }
+    parsed
+}
+
+/// Describes a run that was stopped early, with how far it got if the
+/// solution said, or returns `None` if `error` is not a cancellation.
+fn describe(error: &eyre::Report, timeout: Option<Duration>) -> Option<String> {
+    let cancelled = error.downcast_ref::<Cancelled>()?;
+    let mut description = match (cancelled.reason, timeout) {
+        (CancelReason::TimedOut, Some(timeout)) => {
+            format!("timed out after {}", humantime::format_duration(timeout))
+        }
+        _ => cancelled.to_string(),
     };
-    println!("Part 1: {}", (solution.part1)(input)?);
-    println!("Part 2: {}", (solution.part2)(input)?);
+    // A checkpoint wraps the cancellation with the solution's progress.
+    if error.chain().nth(1).is_some() {
+        description = format!("{} at {}", description, error);
+    }
+    Some(description)
+}
+
+/// The exit status after Ctrl-C, following the shell's 128 + SIGINT.
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Set once the user has pressed Ctrl-C.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// The token of the part being run, for the Ctrl-C handler to cancel.
+static RUNNING: Mutex<Option<CancelToken>> = Mutex::new(None);
+
+fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
+
+/// The exit status of a run that failed, which tells an interrupted run apart.
+fn exit_code() -> i32 {
+    if interrupted() {
+        INTERRUPTED_EXIT_CODE
+    } else {
+        1
+    }
+}
+
+/// Makes the first Ctrl-C stop the running part at its next checkpoint, so
+/// that its progress so far can be printed, and a second one exit at once.
+#[cfg(unix)]
+fn handle_interrupts() -> Result<()> {
+    use signal_hook::{consts::SIGINT, iterator::Signals};
+
+    let mut signals = Signals::new([SIGINT])?;
+    std::thread::spawn(move || {
+        for _ in signals.forever() {
+            if INTERRUPTED.swap(true, Ordering::Relaxed) {
+                process::exit(INTERRUPTED_EXIT_CODE);
+            }
+            if let Some(token) = &*RUNNING.lock().unwrap() {
+                token.cancel(CancelReason::Interrupted);
+            }
+        }
+    });
     Ok(())
 }
+
+/// Leaves Ctrl-C to kill the process where signals are not supported.
+#[cfg(not(unix))]
+fn handle_interrupts() -> Result<()> {
+    Ok(())
+}
+
+/// Draws a progress bar on stderr for each task a solution reports.
+#[derive(Default)]
+struct Bars {
+    multi: MultiProgress,
+    bars: Mutex<Vec<(&'static str, ProgressBar)>>,
+}
+
+impl Bars {
+    fn clear(&self) {
+        for (_, bar) in self.bars.lock().unwrap().drain(..) {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+impl Progress for Bars {
+    fn update(&self, task: &'static str, position: u64, length: Option<u64>) {
+        let mut bars = self.bars.lock().unwrap();
+        let bar = match bars.iter().find(|(name, _)| *name == task) {
+            Some((_, bar)) => bar,
+            None => {
+                let (bar, template) = match length {
+                    Some(_) => (
+                        ProgressBar::new(0),
+                        "{msg:>12} [{bar:40}] {pos}/{len} ({eta})",
+                    ),
+                    None => (ProgressBar::new_spinner(), "{msg:>12} {spinner} {pos}"),
+                };
+                let style = ProgressStyle::with_template(template)
+                    .expect("progress templates are valid")
+                    .progress_chars("=> ");
+                let bar = self.multi.add(bar.with_style(style).with_message(task));
+                bars.push((task, bar));
+                &bars.last().unwrap().1
+            }
+        };
+        if let Some(length) = length {
+            bar.set_length(length);
+        }
+        bar.set_position(position);
+    }
+}
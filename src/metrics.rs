@@ -0,0 +1,132 @@
+//! Counting the work a solution does, such as how many nodes a search
+//! expands, so that algorithms can be compared by more than running time.
+
+use std::{collections::BTreeMap, sync::Mutex};
+
+use serde::Serialize;
+
+use crate::Fact;
+
+/// Receives the measurements a solution reports.
+///
+/// Solutions should add up counts in a local variable and report them once per
+/// search or simulation step rather than once per iteration of a hot loop.
+pub trait Metrics: Send + Sync {
+    /// Adds `amount` to the counter `name`.
+    fn count(&self, name: &'static str, amount: u64);
+    /// Adds one sample to the histogram `name`.
+    fn record(&self, name: &'static str, value: u64);
+}
+
+/// A summary of the samples recorded in a histogram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Histogram {
+    pub count: u64,
+    pub sum: u64,
+    pub min: u64,
+    pub max: u64,
+}
+
+impl Histogram {
+    fn new(value: u64) -> Self {
+        Self {
+            count: 1,
+            sum: value,
+            min: value,
+            max: value,
+        }
+    }
+
+    fn add(&mut self, value: u64) {
+        self.count += 1;
+        self.sum = self.sum.saturating_add(value);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+}
+
+/// Keeps every counter and histogram reported to it.
+#[derive(Debug, Default)]
+pub struct Registry {
+    counters: Mutex<BTreeMap<&'static str, u64>>,
+    histograms: Mutex<BTreeMap<&'static str, Histogram>>,
+}
+
+impl Registry {
+    pub fn counters(&self) -> BTreeMap<&'static str, u64> {
+        self.counters.lock().unwrap().clone()
+    }
+
+    pub fn histograms(&self) -> BTreeMap<&'static str, Histogram> {
+        self.histograms.lock().unwrap().clone()
+    }
+
+    /// Every measurement in name order, for showing alongside facts.
+    pub fn facts(&self) -> Vec<(&'static str, Fact)> {
+        let counters = self
+            .counters()
+            .into_iter()
+            .map(|(name, count)| (name, Fact::from(count)));
+        let histograms = self.histograms().into_iter().map(|(name, histogram)| {
+            let fields = [
+                ("count", histogram.count),
+                ("sum", histogram.sum),
+                ("min", histogram.min),
+                ("max", histogram.max),
+            ];
+            (name, Fact::table(fields))
+        });
+        let mut facts: Vec<_> = counters.chain(histograms).collect();
+        facts.sort_by_key(|(name, _)| *name);
+        facts
+    }
+}
+
+impl Metrics for Registry {
+    fn count(&self, name: &'static str, amount: u64) {
+        *self.counters.lock().unwrap().entry(name).or_default() += amount;
+    }
+
+    fn record(&self, name: &'static str, value: u64) {
+        self.histograms
+            .lock()
+            .unwrap()
+            .entry(name)
+            .and_modify(|histogram| histogram.add(value))
+            .or_insert_with(|| Histogram::new(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry() {
+        let registry = Registry::default();
+        registry.count("ticks", 2);
+        registry.count("ticks", 3);
+        for value in [4, 1, 7] {
+            registry.record("nodes", value);
+        }
+        assert_eq!(Some(&5), registry.counters().get("ticks"));
+        assert_eq!(
+            Histogram {
+                count: 3,
+                sum: 12,
+                min: 1,
+                max: 7
+            },
+            registry.histograms()["nodes"]
+        );
+        assert_eq!(
+            "nodes: {count: 3, sum: 12, min: 1, max: 7}, ticks: 5",
+            registry
+                .facts()
+                .iter()
+                .map(|(name, fact)| format!("{}: {}", name, fact))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use std::{collections::BTreeMap, fmt};
+
+use eyre::{bail, eyre, Result};
+
+/// Named values that tune a day's solution, such as day 7's number of workers.
+///
+/// Values are kept as written and parsed by the solution that reads them.
+pub type Params = BTreeMap<String, String>;
+
+/// A named integer that tunes a day's solution, such as day 7's number of
+/// workers, which can be overridden from the command line or config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Param {
+    pub name: &'static str,
+    pub default: i64,
+    /// The smallest and largest values the solution accepts.
+    pub range: (i64, i64),
+    pub help: &'static str,
+}
+
+impl Param {
+    pub const fn new(name: &'static str, default: i64, help: &'static str) -> Self {
+        Self {
+            name,
+            default,
+            range: (i64::MIN, i64::MAX),
+            help,
+        }
+    }
+
+    pub const fn at_least(mut self, min: i64) -> Self {
+        self.range.0 = min;
+        self
+    }
+
+    pub const fn at_most(mut self, max: i64) -> Self {
+        self.range.1 = max;
+        self
+    }
+
+    /// Parses an override, checking it is in range.
+    pub fn parse(&self, value: &str) -> Result<i64> {
+        let parsed: i64 = value.parse().map_err(|_| {
+            eyre!(
+                "Parameter {} must be an integer, got {:?}",
+                self.name,
+                value
+            )
+        })?;
+        let (min, max) = self.range;
+        if parsed < min {
+            bail!(
+                "Parameter {} must be at least {}, got {}",
+                self.name,
+                min,
+                parsed
+            );
+        }
+        if parsed > max {
+            bail!(
+                "Parameter {} must be at most {}, got {}",
+                self.name,
+                max,
+                parsed
+            );
+        }
+        Ok(parsed)
+    }
+}
+
+/// Shows the parameter as `name (default 5): help`.
+impl fmt::Display for Param {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (default {}): {}", self.name, self.default, self.help)
+    }
+}
+
+/// Checks that every override in `params` names one of `declared` and is a
+/// valid value for it.
+pub fn check_params(declared: &[Param], params: &Params) -> Result<()> {
+    for (name, value) in params {
+        match declared.iter().find(|param| param.name == name) {
+            Some(param) => {
+                param.parse(value)?;
+            }
+            None if declared.is_empty() => bail!("This day has no parameters, got {:?}", name),
+            None => bail!(
+                "Unknown parameter {:?}, expected one of:\n  {}",
+                name,
+                declared
+                    .iter()
+                    .map(Param::to_string)
+                    .collect::<Vec<_>>()
+                    .join("\n  ")
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// An example from the puzzle text, with the parameters it was worked with.
+#[derive(Debug, Clone, Copy)]
+pub struct Example {
+    pub input: &'static str,
+    /// Overrides for the day's parameters, which examples often scale down.
+    pub params: &'static [(&'static str, i64)],
+    /// The answer to each part given in the puzzle text, if there is one.
+    pub answers: [Option<&'static str>; 2],
+}
+
+impl Example {
+    pub fn params(&self) -> Params {
+        self.params
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{Context, SOLUTIONS};
+
+    const WORKERS: Param = Param::new("workers", 5, "How many elves work at once").at_least(1);
+
+    #[test]
+    fn overrides() {
+        let params: Params = [("workers".to_string(), "2".to_string())].into();
+        let ctx = Context::default().with_params(Arc::new(params.clone()));
+        assert_eq!(2, ctx.param::<usize>(&WORKERS).unwrap());
+        assert_eq!(5, Context::default().param::<u8>(&WORKERS).unwrap());
+        check_params(&[WORKERS], &params).unwrap();
+
+        let zero = [("workers".to_string(), "0".to_string())].into();
+        let error = check_params(&[WORKERS], &zero).unwrap_err();
+        assert_eq!(
+            "Parameter workers must be at least 1, got 0",
+            error.to_string()
+        );
+        let unknown = [("elves".to_string(), "2".to_string())].into();
+        assert!(check_params(&[WORKERS], &unknown).is_err());
+    }
+
+    #[test]
+    fn examples() {
+        for (day, solution) in SOLUTIONS.iter().enumerate() {
+            for example in solution.examples {
+                check_params(solution.params, &example.params()).unwrap();
+                let ctx = Context::default().with_params(Arc::new(example.params()));
+                for (variants, expected) in solution.parts().iter().zip(example.answers) {
+                    let expected = match expected {
+                        Some(expected) => expected,
+                        None => continue,
+                    };
+                    for variant in *variants {
+                        let answer = (variant.run)(example.input, &ctx).unwrap();
+                        assert_eq!(
+                            expected,
+                            answer.trim_end(),
+                            "day {} variant {}",
+                            day + 1,
+                            variant.name
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
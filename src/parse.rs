@@ -0,0 +1,236 @@
+//! Small parser combinators for the puzzles' line formats.
+//!
+//! A parser is any function from the remaining input to the rest of the input
+//! and a value, so parsers compose by calling one after another:
+//!
+//! ```
+//! use aoc2018::parse::{self, int, literal, PResult};
+//!
+//! fn pair(input: &str) -> PResult<'_, (i32, i32)> {
+//!     let (input, x) = int(input)?;
+//!     let (input, _) = literal(",")(input)?;
+//!     let (input, y) = int(input)?;
+//!     Ok((input, (x, y)))
+//! }
+//!
+//! assert_eq!((3, -4), parse::complete("3,-4", pair).unwrap());
+//! let error = parse::lines("1,2\n3;4", pair).unwrap_err();
+//! assert_eq!("Line 2, column 2: expected \",\", found \";4\"", error.to_string());
+//! ```
+//!
+//! Parsers only borrow from the input, and fail with the position of the
+//! failure for [`complete`] and [`lines`] to report.
+
+use std::{fmt, str::FromStr};
+
+use eyre::{eyre, Report, Result};
+
+/// The rest of the input and the parsed value, or where parsing failed.
+pub type PResult<'a, T> = std::result::Result<(&'a str, T), Expected<'a>>;
+
+/// A parse failure: what was expected at the start of `found`, which is the
+/// rest of the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Expected<'a> {
+    pub what: What,
+    pub found: &'a str,
+}
+
+/// What a parser expected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum What {
+    /// Exactly this text.
+    Literal(&'static str),
+    /// Something described in words, such as "an integer".
+    Described(&'static str),
+}
+
+impl Expected<'_> {
+    /// Describes the failure at its line and column within `input`.
+    fn report(&self, input: &str, line: usize) -> Report {
+        let offset = input.len() - self.found.len();
+        let column = input[..offset].chars().count() + 1;
+        eyre!("Line {}, column {}: {}", line, column, self)
+    }
+}
+
+impl fmt::Display for Expected<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.what {
+            What::Literal(literal) => write!(f, "expected {:?}", literal)?,
+            What::Described(what) => write!(f, "expected {}", what)?,
+        }
+        match self.found.lines().next().unwrap_or("") {
+            "" => write!(f, ", found the end of the line"),
+            found => write!(f, ", found {:?}", found),
+        }
+    }
+}
+
+/// Runs `parser` on the whole of `input`, which may end in a newline.
+pub fn complete<'a, T>(input: &'a str, parser: impl Fn(&'a str) -> PResult<'a, T>) -> Result<T> {
+    line(input.strip_suffix('\n').unwrap_or(input), 1, parser)
+}
+
+/// Runs `parser` on every line of `input`, each of which it must consume
+/// completely.
+pub fn lines<'a, T>(input: &'a str, parser: impl Fn(&'a str) -> PResult<'a, T>) -> Result<Vec<T>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, input)| line(input, i + 1, &parser))
+        .collect()
+}
+
+/// Runs `parser` on the whole of `input`, the line numbered `number` from 1
+/// in error messages.
+pub fn line<'a, T>(
+    input: &'a str,
+    number: usize,
+    parser: impl Fn(&'a str) -> PResult<'a, T>,
+) -> Result<T> {
+    let (rest, value) = parser(input).map_err(|expected| expected.report(input, number))?;
+    end(rest).map_err(|expected| expected.report(input, number))?;
+    Ok(value)
+}
+
+/// Succeeds only at the end of the input.
+pub fn end(input: &str) -> PResult<'_, ()> {
+    if input.is_empty() {
+        Ok((input, ()))
+    } else {
+        Err(Expected {
+            what: What::Described("the end of the line"),
+            found: input,
+        })
+    }
+}
+
+/// Matches exactly `expected`.
+pub fn literal<'a>(expected: &'static str) -> impl Fn(&'a str) -> PResult<'a, ()> {
+    move |input: &'a str| match input.strip_prefix(expected) {
+        Some(rest) => Ok((rest, ())),
+        None => Err(Expected {
+            what: What::Literal(expected),
+            found: input,
+        }),
+    }
+}
+
+/// Skips any spaces and tabs, including none.
+pub fn whitespace(input: &str) -> PResult<'_, ()> {
+    Ok((input.trim_start_matches([' ', '\t']), ()))
+}
+
+/// Matches one character satisfying `predicate`, described by `what`.
+pub fn char_if<'a>(
+    what: &'static str,
+    predicate: impl Fn(char) -> bool,
+) -> impl Fn(&'a str) -> PResult<'a, char> {
+    move |input: &'a str| {
+        let mut chars = input.chars();
+        match chars.next() {
+            Some(c) if predicate(c) => Ok((chars.as_str(), c)),
+            _ => Err(Expected {
+                what: What::Described(what),
+                found: input,
+            }),
+        }
+    }
+}
+
+/// Parses an unsigned integer of decimal digits.
+pub fn uint<T: FromStr>(input: &str) -> PResult<'_, T> {
+    number(input, 0, "an unsigned integer")
+}
+
+/// Parses a decimal integer with an optional `+` or `-` sign.
+pub fn int<T: FromStr>(input: &str) -> PResult<'_, T> {
+    let sign = usize::from(input.starts_with(['+', '-']));
+    number(input, sign, "an integer")
+}
+
+/// Parses the digits after the first `sign` bytes of `input` along with them.
+fn number<'a, T: FromStr>(input: &'a str, sign: usize, what: &'static str) -> PResult<'a, T> {
+    let digits = input[sign..]
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len() - sign);
+    if digits == 0 {
+        return Err(Expected {
+            what: What::Described(what),
+            found: input,
+        });
+    }
+    let (number, rest) = input.split_at(sign + digits);
+    // `+` is accepted by `FromStr` for every integer type.
+    match number.parse() {
+        Ok(value) => Ok((rest, value)),
+        Err(_) => Err(Expected {
+            what: What::Described("a smaller integer"),
+            found: input,
+        }),
+    }
+}
+
+/// Parses one or more `item`s separated by `separator`, adding them to a
+/// collection such as a `Vec` or an array-backed one.
+pub fn separated<'a, T, C: Default + Extend<T>>(
+    item: impl Fn(&'a str) -> PResult<'a, T>,
+    separator: impl Fn(&'a str) -> PResult<'a, ()>,
+) -> impl Fn(&'a str) -> PResult<'a, C> {
+    move |input: &'a str| {
+        let (mut input, first) = item(input)?;
+        let mut items = C::default();
+        items.extend([first]);
+        while let Ok((rest, ())) = separator(input) {
+            let (rest, next) = item(rest)?;
+            items.extend([next]);
+            input = rest;
+        }
+        Ok((input, items))
+    }
+}
+
+/// Parses `inner` surrounded by optional spaces, as in `< 3>`.
+pub fn padded<'a, T>(
+    inner: impl Fn(&'a str) -> PResult<'a, T>,
+) -> impl Fn(&'a str) -> PResult<'a, T> {
+    move |input: &'a str| {
+        let (input, ()) = whitespace(input)?;
+        let (input, value) = inner(input)?;
+        let (input, ()) = whitespace(input)?;
+        Ok((input, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combinators() {
+        assert_eq!(Ok(("x", -12)), int::<i32>("-12x"));
+        assert_eq!(Ok(("", 7)), int::<u8>("+7"));
+        assert_eq!(Ok(("", 7)), uint::<u8>("7"));
+        assert!(uint::<u8>("-7").is_err());
+        assert!(int::<i32>("-").is_err());
+        assert_eq!(
+            "expected a smaller integer, found \"300\"",
+            uint::<u8>("300").unwrap_err().to_string()
+        );
+        assert_eq!(Ok(("b", ())), literal("a")("ab"));
+        assert_eq!(
+            Ok(("", 'Q')),
+            char_if("a capital letter", |c| c.is_ascii_uppercase())("Q")
+        );
+        assert_eq!(Ok(("", 3)), padded(int::<i32>)("  3 "));
+
+        let list = separated::<i32, Vec<_>>(int, literal(", "));
+        assert_eq!(Ok((";", vec![1, -2, 3])), list("1, -2, 3;"));
+        let error = complete("1, 2,3", list).unwrap_err();
+        assert_eq!(
+            "Line 1, column 5: expected the end of the line, found \",3\"",
+            error.to_string()
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! Drawing spatial puzzle data as images, for days where text is too dense to
+//! read.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use eyre::{bail, Result};
+
+use crate::{
+    grid::{Grid, Point},
+    Context,
+};
+
+/// An image is a grid of pixels.
+pub type Image = Grid<Rgb>;
+
+/// Draws a day's input as an image.
+pub type Render = fn(&str, &Context) -> Result<Image>;
+
+/// An 8-bit sRGB colour.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+}
+
+/// Colours shared by the days' renderers so that the images look alike.
+pub mod palette {
+    use super::Rgb;
+
+    pub const BACKGROUND: Rgb = Rgb(16, 16, 24);
+    /// Cells that nothing owns, such as points tied between two owners.
+    pub const UNOWNED: Rgb = Rgb(200, 200, 200);
+    pub const WALL: Rgb = Rgb(90, 70, 50);
+    pub const FLOOR: Rgb = Rgb(40, 40, 48);
+    pub const ELF: Rgb = Rgb(60, 200, 90);
+    pub const GOBLIN: Rgb = Rgb(220, 60, 60);
+
+    /// A distinct colour for each of a set of owners.
+    ///
+    /// Hues are spaced by the golden angle so that neighbouring indices stay
+    /// easy to tell apart however many owners there are.
+    pub fn owner(index: usize) -> Rgb {
+        let hue = (index as f64 * 137.507_764) % 360.0;
+        hsv(hue, 0.65, 0.95)
+    }
+
+    /// A colour on a dark-blue to red to yellow ramp, where `value` is clamped
+    /// to `0.0..=1.0`.
+    pub fn heat(value: f64) -> Rgb {
+        let value = if value.is_nan() {
+            0.0
+        } else {
+            value.clamp(0.0, 1.0)
+        };
+        hsv(240.0 + value * 180.0, 0.9, 0.25 + value * 0.75)
+    }
+
+    fn hsv(hue: f64, saturation: f64, value: f64) -> Rgb {
+        let hue = hue.rem_euclid(360.0) / 60.0;
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let (r, g, b) = match hue as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = value - chroma;
+        let channel = |c: f64| ((c + m) * 255.0).round() as u8;
+        Rgb(channel(r), channel(g), channel(b))
+    }
+}
+
+/// Scales `image` up by an integer `factor`, drawing each pixel as a square.
+pub fn upscale(image: &Image, factor: usize) -> Image {
+    let factor = factor.max(1);
+    Grid::from_fn(image.width() * factor, image.height() * factor, |point| {
+        image[Point::new(point.x / factor, point.y / factor)]
+    })
+}
+
+/// Writes `image` as a binary PPM (`P6`) file.
+pub fn write_ppm(image: &Image, mut out: impl Write) -> io::Result<()> {
+    write!(out, "P6\n{} {}\n255\n", image.width(), image.height())?;
+    out.write_all(&pixel_bytes(image))
+}
+
+/// Writes `image` as an 8-bit RGB PNG file.
+pub fn write_png(image: &Image, out: impl Write) -> Result<()> {
+    let mut encoder = png::Encoder::new(out, image.width() as u32, image.height() as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&pixel_bytes(image))?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Saves `image` to `path`, choosing the format from its `.png` or `.ppm`
+/// extension.
+pub fn save(image: &Image, path: &Path) -> Result<()> {
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    let is_png = match extension.map(str::to_ascii_lowercase).as_deref() {
+        Some("png") => true,
+        Some("ppm") => false,
+        _ => bail!("Can only save images as .png or .ppm, not {:?}", path),
+    };
+    let mut out = BufWriter::new(File::create(path)?);
+    if is_png {
+        write_png(image, &mut out)?;
+    } else {
+        write_ppm(image, &mut out)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn pixel_bytes(image: &Image) -> Vec<u8> {
+    image
+        .iter()
+        .flat_map(|(_, &Rgb(r, g, b))| [r, g, b])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkerboard() -> Image {
+        Grid::from_fn(3, 2, |point| {
+            if (point.x + point.y) % 2 == 0 {
+                Rgb::BLACK
+            } else {
+                Rgb::WHITE
+            }
+        })
+    }
+
+    #[test]
+    fn ppm() {
+        let mut out = Vec::new();
+        write_ppm(&checkerboard(), &mut out).unwrap();
+        let (header, pixels) = out.split_at(11);
+        assert_eq!(b"P6\n3 2\n255\n", header);
+        assert_eq!(&[0, 0, 0, 255, 255, 255, 0, 0, 0], &pixels[..9]);
+        assert_eq!(18, pixels.len());
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let mut out = Vec::new();
+        write_png(&checkerboard(), &mut out).unwrap();
+        let mut reader = png::Decoder::new(&out[..]).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((3, 2), (info.width, info.height));
+        assert_eq!(pixel_bytes(&checkerboard()), pixels);
+    }
+
+    #[test]
+    fn upscaling() {
+        let image = upscale(&checkerboard(), 2);
+        assert_eq!((6, 4), (image.width(), image.height()));
+        assert_eq!(Rgb::WHITE, image[Point::new(3, 1)]);
+        assert_eq!(Rgb::BLACK, image[Point::new(4, 1)]);
+    }
+
+    #[test]
+    fn owners_are_distinct() {
+        let colours: std::collections::HashSet<_> = (0..50).map(palette::owner).collect();
+        assert_eq!(50, colours.len());
+    }
+}
@@ -0,0 +1,324 @@
+//! Shortest path searches over graphs given by a neighbour function.
+//!
+//! Every search breaks ties by the nodes' [`Ord`]: of the goals at the same
+//! cost the smallest is found, and of the equally short paths to a node the
+//! one through the smallest parent is kept. Grid searches therefore follow
+//! reading order, since [`Point`](crate::grid::Point)s are ordered that way.
+//!
+//! A [`Search`] keeps its buffers between searches, so reusing one in a loop
+//! does not allocate once they have grown.
+
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::Entry, BinaryHeap},
+    hash::Hash,
+    mem,
+};
+
+use crate::hash::HashMap;
+
+/// A goal reached by a search, and the cost of the cheapest path to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Found<N> {
+    pub node: N,
+    pub cost: u64,
+}
+
+/// Scratch space for searches, which remembers the last search's paths.
+#[derive(Debug, Clone)]
+pub struct Search<N> {
+    /// The cheapest known cost of each reached node and the node before it.
+    reached: HashMap<N, (u64, Option<N>)>,
+    frontier: Vec<N>,
+    next: Vec<N>,
+    /// Nodes waiting to be expanded by estimated total cost, then node, then
+    /// cost so far.
+    queue: BinaryHeap<Reverse<(u64, N, u64)>>,
+    expanded: usize,
+}
+
+impl<N> Default for Search<N> {
+    fn default() -> Self {
+        Self {
+            reached: HashMap::default(),
+            frontier: Vec::new(),
+            next: Vec::new(),
+            queue: BinaryHeap::new(),
+            expanded: 0,
+        }
+    }
+}
+
+impl<N: Copy + Ord + Hash> Search<N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Finds the nearest goal from `starts` where every edge costs 1, by
+    /// expanding the nodes at each distance in order.
+    ///
+    /// ```
+    /// use aoc2018::search::{Found, Search};
+    ///
+    /// let mut search = Search::new();
+    /// let neighbours = |n: u32| [n + 1, n * 2];
+    /// let found = search.bfs([1], neighbours, |n| n == 10);
+    /// assert_eq!(Some(Found { node: 10, cost: 4 }), found);
+    /// assert_eq!(vec![1, 2, 4, 5, 10], search.path(10));
+    /// ```
+    pub fn bfs<I>(
+        &mut self,
+        starts: impl IntoIterator<Item = N>,
+        mut neighbours: impl FnMut(N) -> I,
+        mut is_goal: impl FnMut(N) -> bool,
+    ) -> Option<Found<N>>
+    where
+        I: IntoIterator<Item = N>,
+    {
+        self.reset(starts);
+        let mut frontier = mem::take(&mut self.frontier);
+        let mut next = mem::take(&mut self.next);
+        frontier.extend(self.reached.keys().copied());
+        let mut cost = 0;
+        let mut found = None;
+        while !frontier.is_empty() {
+            frontier.sort_unstable();
+            if let Some(&node) = frontier.iter().find(|&&node| is_goal(node)) {
+                found = Some(Found { node, cost });
+                break;
+            }
+            for &node in &frontier {
+                self.expanded += 1;
+                for neighbour in neighbours(node) {
+                    // The frontier is sorted, so the first parent to reach
+                    // a node is the smallest.
+                    if let Entry::Vacant(entry) = self.reached.entry(neighbour) {
+                        entry.insert((cost + 1, Some(node)));
+                        next.push(neighbour);
+                    }
+                }
+            }
+            frontier.clear();
+            mem::swap(&mut frontier, &mut next);
+            cost += 1;
+        }
+        frontier.clear();
+        self.frontier = frontier;
+        self.next = next;
+        found
+    }
+
+    /// Finds the cheapest goal from `starts`, where `neighbours` gives each
+    /// neighbour with the cost of the edge to it.
+    pub fn dijkstra<I>(
+        &mut self,
+        starts: impl IntoIterator<Item = N>,
+        neighbours: impl FnMut(N) -> I,
+        is_goal: impl FnMut(N) -> bool,
+    ) -> Option<Found<N>>
+    where
+        I: IntoIterator<Item = (N, u64)>,
+    {
+        self.astar(starts, neighbours, |_| 0, is_goal)
+    }
+
+    /// Like [`Self::dijkstra`], but expands first the nodes that `heuristic`
+    /// estimates are closest to a goal.
+    ///
+    /// The heuristic must never overestimate the remaining cost, nor drop by
+    /// more than an edge's cost along it, or worse paths may be found.
+    ///
+    /// ```
+    /// use aoc2018::search::Search;
+    ///
+    /// // Walking costs 1 a step and jumping 3 costs 2.
+    /// let neighbours = |n: i32| [(n - 1, 1), (n + 1, 1), (n + 3, 2)];
+    /// let heuristic = |n: i32| (20 - n).unsigned_abs() as u64 * 2 / 3;
+    /// let found = Search::new().astar([0], neighbours, heuristic, |n| n == 20);
+    /// assert_eq!(14, found.unwrap().cost);
+    /// ```
+    pub fn astar<I>(
+        &mut self,
+        starts: impl IntoIterator<Item = N>,
+        mut neighbours: impl FnMut(N) -> I,
+        mut heuristic: impl FnMut(N) -> u64,
+        mut is_goal: impl FnMut(N) -> bool,
+    ) -> Option<Found<N>>
+    where
+        I: IntoIterator<Item = (N, u64)>,
+    {
+        self.reset(starts);
+        let mut queue = mem::take(&mut self.queue);
+        queue.extend(
+            self.reached
+                .keys()
+                .map(|&start| Reverse((heuristic(start), start, 0))),
+        );
+        let mut found = None;
+        while let Some(Reverse((_, node, cost))) = queue.pop() {
+            if cost > self.reached[&node].0 {
+                // A cheaper path to this node was found after it was queued.
+                continue;
+            }
+            if is_goal(node) {
+                found = Some(Found { node, cost });
+                break;
+            }
+            self.expanded += 1;
+            for (neighbour, edge) in neighbours(node) {
+                let new_cost = cost + edge;
+                let better = match self.reached.get(&neighbour) {
+                    Some(&(old_cost, old_parent)) => {
+                        new_cost < old_cost
+                            || (new_cost == old_cost && old_parent.is_some_and(|p| node < p))
+                    }
+                    None => true,
+                };
+                if better {
+                    let queued = self.reached.insert(neighbour, (new_cost, Some(node)));
+                    if queued.is_none_or(|(old_cost, _)| new_cost < old_cost) {
+                        queue.push(Reverse((
+                            new_cost + heuristic(neighbour),
+                            neighbour,
+                            new_cost,
+                        )));
+                    }
+                }
+            }
+        }
+        queue.clear();
+        self.queue = queue;
+        found
+    }
+
+    /// The cost of the cheapest path the last search found to `node`, if it
+    /// reached it.
+    pub fn cost(&self, node: N) -> Option<u64> {
+        self.reached.get(&node).map(|&(cost, _)| cost)
+    }
+
+    /// The path the last search took from a start to `node`, including both,
+    /// or an empty path if it did not reach `node`.
+    pub fn path(&self, node: N) -> Vec<N> {
+        let mut path = Vec::new();
+        let mut next = self.reached.get(&node).map(|_| node);
+        while let Some(node) = next {
+            path.push(node);
+            next = self.reached[&node].1;
+        }
+        path.reverse();
+        path
+    }
+
+    /// Forgets the last search's paths, keeping the buffers for the next.
+    pub fn clear(&mut self) {
+        self.reset([]);
+    }
+
+    /// How many nodes the last search expanded.
+    pub fn expanded(&self) -> usize {
+        self.expanded
+    }
+
+    fn reset(&mut self, starts: impl IntoIterator<Item = N>) {
+        self.reached.clear();
+        self.expanded = 0;
+        for start in starts {
+            self.reached.insert(start, (0, None));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{Grid, Point};
+
+    const MAZE: &str = "\
+        #######\n\
+        #.....#\n\
+        #.#.#.#\n\
+        #.....#\n\
+        #######\n\
+    ";
+
+    fn open_neighbours(maze: &Grid<bool>, point: Point) -> impl Iterator<Item = Point> + '_ {
+        maze.neighbours4(point)
+            .filter(|&neighbour| !maze[neighbour])
+    }
+
+    #[test]
+    fn ties_follow_reading_order() {
+        let maze: Grid<bool> = Grid::parse(MAZE, |_, c| Ok(c == '#')).unwrap();
+        let mut search = Search::new();
+        let start = Point::new(3, 3);
+        // Both top corners are 4 steps away, and each has two shortest paths.
+        let corners = [Point::new(1, 1), Point::new(5, 1)];
+        let found = search.bfs(
+            [start],
+            |p| open_neighbours(&maze, p),
+            |p| corners.contains(&p),
+        );
+        assert_eq!(
+            Some(Found {
+                node: corners[0],
+                cost: 4
+            }),
+            found
+        );
+        assert_eq!(
+            vec![
+                start,
+                Point::new(3, 2),
+                Point::new(3, 1),
+                Point::new(2, 1),
+                corners[0]
+            ],
+            search.path(corners[0])
+        );
+        assert_eq!(Some(2), search.cost(Point::new(3, 1)));
+
+        let weighted = |p| open_neighbours(&maze, p).map(|n| (n, 1));
+        let found = search.dijkstra([start], weighted, |p| corners.contains(&p));
+        assert_eq!(
+            Some(Found {
+                node: corners[0],
+                cost: 4
+            }),
+            found
+        );
+        assert_eq!(5, search.path(corners[0]).len());
+        assert_eq!(
+            vec![
+                start,
+                Point::new(3, 2),
+                Point::new(3, 1),
+                Point::new(2, 1),
+                corners[0]
+            ],
+            search.path(corners[0])
+        );
+
+        let unreachable = Point::new(0, 0);
+        assert_eq!(
+            None,
+            search.bfs([start], |p| open_neighbours(&maze, p), |p| p == unreachable)
+        );
+        assert!(search.path(unreachable).is_empty());
+    }
+
+    #[test]
+    fn astar_agrees_with_dijkstra() {
+        let mut search = Search::new();
+        // Stepping costs 2 and jumping 5 ahead costs 7.
+        let neighbours = |n: i64| [(n - 1, 2), (n + 1, 2), (n + 5, 7)];
+        for goal in [0, 1, 17, 40, 99] {
+            let cheapest = search.dijkstra([3], neighbours, |n| n == goal).unwrap();
+            let dijkstra_expanded = search.expanded();
+            let heuristic = |n: i64| (goal - n).unsigned_abs() * 7 / 5;
+            let found = search.astar([3], neighbours, heuristic, |n| n == goal);
+            assert_eq!(Some(cheapest), found);
+            assert!(search.expanded() <= dijkstra_expanded);
+        }
+    }
+}
@@ -0,0 +1,479 @@
+//! A small HTTP server for running the solutions without a Rust toolchain.
+//!
+//! It speaks just enough HTTP/1.1 for `curl` and browsers, answering one
+//! request per connection:
+//!
+//! - `GET /days` lists the solved days with their variants and parameters.
+//! - `POST /day/{n}/part/{p}` solves part `p` of day `n` for the input in the
+//!   request body. The query string may choose a `variant` and override the
+//!   day's parameters, e.g. `/day/7/part/2?workers=2&base_time=0`.
+//!
+//! Every response is a JSON object, with an `error` field if the request
+//! failed.
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    num::NonZeroUsize,
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde_json::{json, Value};
+
+use crate::{find_variant, input, Cancelled, Context, Params, SOLUTIONS};
+
+/// How long a part may run by default before it is cancelled.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The largest request body accepted, far larger than any puzzle input.
+const MAX_BODY: usize = 16 << 20;
+
+/// The most bytes read for the request line and headers together.
+const MAX_HEADER: u64 = 64 << 10;
+
+/// How long a client may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct Server {
+    listener: TcpListener,
+    /// How long a part may run before it is cancelled.
+    timeout: Duration,
+    /// How many requests are answered at once. Further connections wait to be
+    /// accepted until a worker is free.
+    workers: usize,
+}
+
+impl Server {
+    /// Listens on `addr`, which should be a loopback address unless the
+    /// solvers are meant to be reachable from other machines.
+    ///
+    /// Requests are answered by one worker per available CPU.
+    pub fn bind(addr: impl ToSocketAddrs, timeout: Duration) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            timeout,
+            workers: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        })
+    }
+
+    /// The address the server is listening on, which tells the port chosen
+    /// when binding to port 0.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Answers requests forever, on a fixed number of worker threads that
+    /// each accept one connection at a time.
+    pub fn run(self) -> io::Result<()> {
+        let server = Arc::new(self);
+        let workers: Vec<_> = (0..server.workers)
+            .map(|_| {
+                let server = server.clone();
+                thread::spawn(move || server.work())
+            })
+            .collect();
+        for worker in workers {
+            worker
+                .join()
+                .map_err(|_| io::Error::other("A server worker panicked"))?;
+        }
+        Ok(())
+    }
+
+    fn work(&self) {
+        for stream in self.listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(error) => {
+                    tracing::warn!(%error, "Could not accept connection");
+                    continue;
+                }
+            };
+            if let Err(error) = self.serve(stream) {
+                tracing::warn!(%error, "Could not answer request");
+            }
+        }
+    }
+
+    fn serve(&self, stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        let mut reader = BufReader::new(stream);
+        let (status, body) = match read_request(&mut reader) {
+            Ok(request) => {
+                let (status, body) = self.handle(&request);
+                tracing::info!(
+                    method = %request.method,
+                    target = %request.target,
+                    status,
+                    "Answered request"
+                );
+                (status, body)
+            }
+            Err(error) => (400, json!({ "error": error })),
+        };
+        let body = serde_json::to_string_pretty(&body)? + "\n";
+        let mut stream = reader.into_inner();
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\n\
+             Content-Type: application/json\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\
+             \r\n\
+             {}",
+            status,
+            reason(status),
+            body.len(),
+            body
+        )?;
+        stream.flush()
+    }
+
+    fn handle(&self, request: &Request) -> (u16, Value) {
+        let (path, query) = request
+            .target
+            .split_once('?')
+            .unwrap_or((&request.target, ""));
+        let segments: Vec<_> = path.split('/').filter(|s| !s.is_empty()).collect();
+        match (request.method.as_str(), segments.as_slice()) {
+            ("GET", ["days"]) => (200, days()),
+            ("POST", ["day", day, "part", part]) => self.solve(day, part, query, &request.body),
+            (_, ["days"]) | (_, ["day", _, "part", _]) => {
+                (405, json!({ "error": "Method not allowed" }))
+            }
+            _ => (
+                404,
+                json!({ "error": format!("No such endpoint {}", path) }),
+            ),
+        }
+    }
+
+    fn solve(&self, day: &str, part: &str, query: &str, body: &[u8]) -> (u16, Value) {
+        let bad_request = |error: String| (400, json!({ "error": error }));
+        let (day, solution) = match day.parse::<usize>() {
+            Ok(n) if (1..=SOLUTIONS.len()).contains(&n) && SOLUTIONS[n - 1].is_solved() => {
+                (n, &SOLUTIONS[n - 1])
+            }
+            _ => {
+                return (
+                    404,
+                    json!({ "error": format!("Day {} is not solved", day) }),
+                )
+            }
+        };
+        let (part, variants) = match part {
+            "1" => (1, solution.part1),
+            "2" => (2, solution.part2),
+            _ => {
+                return (
+                    404,
+                    json!({ "error": format!("Part {} does not exist", part) }),
+                )
+            }
+        };
+        let mut variant_name = None;
+        let mut params = Params::new();
+        for (name, value) in parse_query(query) {
+            if name == "variant" {
+                variant_name = Some(value);
+            } else {
+                params.insert(name, value);
+            }
+        }
+        let variant = match find_variant(variants, variant_name.as_deref()) {
+            Some(variant) => variant,
+            None => {
+                return bad_request(format!(
+                    "Unknown variant {:?}, expected one of {:?}",
+                    variant_name.unwrap_or_default(),
+                    variants.iter().map(|v| v.name).collect::<Vec<_>>()
+                ))
+            }
+        };
+        if let Err(error) = solution.check_params(&params) {
+            return bad_request(format!("{:#}", error));
+        }
+        let input = match std::str::from_utf8(body) {
+            Ok(input) => input::normalize(input),
+            Err(_) => return bad_request("The input is not valid UTF-8".to_string()),
+        };
+
+        let ctx = Context::default().with_params(Arc::new(params));
+        ctx.cancel_token().cancel_after(self.timeout);
+        let start = Instant::now();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| (variant.run)(&input, &ctx)));
+        let seconds = start.elapsed().as_secs_f64();
+        let mut response = json!({
+            "day": day,
+            "part": part,
+            "variant": variant.name,
+            "seconds": seconds,
+        });
+        match result {
+            Ok(Ok(answer)) => {
+                response["answer"] = answer.into();
+                (200, response)
+            }
+            Err(_) => {
+                response["error"] = "The solution panicked".into();
+                (500, response)
+            }
+            Ok(Err(error)) => {
+                response["error"] = match error.downcast_ref::<Cancelled>() {
+                    // A checkpoint wraps the cancellation with how far it got.
+                    Some(cancelled) if error.chain().nth(1).is_some() => {
+                        format!("{} after {:.1}s at {}", cancelled, seconds, error)
+                    }
+                    Some(cancelled) => format!("{} after {:.1}s", cancelled, seconds),
+                    None => format!("{:#}", error),
+                }
+                .into();
+                (422, response)
+            }
+        }
+    }
+}
+
+/// Every solved day, with the names of its variants and its parameters.
+fn days() -> Value {
+    let days: Vec<_> = SOLUTIONS
+        .iter()
+        .enumerate()
+        .filter(|(_, solution)| solution.is_solved())
+        .map(|(i, solution)| {
+            let parts: Vec<Vec<_>> = solution
+                .parts()
+                .iter()
+                .map(|variants| variants.iter().map(|variant| variant.name).collect())
+                .collect();
+            let params: Vec<_> = solution
+                .params
+                .iter()
+                .map(|param| {
+                    json!({ "name": param.name, "default": param.default, "help": param.help })
+                })
+                .collect();
+            json!({ "day": i + 1, "parts": parts, "params": params })
+        })
+        .collect();
+    json!({ "days": days })
+}
+
+struct Request {
+    method: String,
+    target: String,
+    body: Vec<u8>,
+}
+
+/// Reads a request line, headers and a body of `Content-Length` bytes.
+fn read_request(reader: &mut impl BufRead) -> Result<Request, String> {
+    let mut head = reader.by_ref().take(MAX_HEADER);
+    let mut line = String::new();
+    read_header_line(&mut head, &mut line)
+        .map_err(|error| format!("Could not read request: {}", error))?;
+    let mut words = line.split_whitespace();
+    let (method, target) = match (words.next(), words.next()) {
+        (Some(method), Some(target)) => (method.to_string(), target.to_string()),
+        _ => return Err(format!("Invalid request line {:?}", line.trim_end())),
+    };
+    let mut length = 0;
+    loop {
+        line.clear();
+        read_header_line(&mut head, &mut line)
+            .map_err(|error| format!("Could not read headers: {}", error))?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("Invalid Content-Length {:?}", value.trim()))?;
+            }
+        }
+    }
+    if length > MAX_BODY {
+        return Err(format!("The input is over {} bytes", MAX_BODY));
+    }
+    let mut body = vec![0; length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|error| format!("Could not read body: {}", error))?;
+    Ok(Request {
+        method,
+        target,
+        body,
+    })
+}
+
+/// Reads a line of the request head, which must end before [`MAX_HEADER`]
+/// bytes have been read.
+fn read_header_line(head: &mut impl BufRead, line: &mut String) -> io::Result<()> {
+    head.read_line(line)?;
+    if !line.ends_with('\n') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "the request head ended early or is over {} bytes",
+                MAX_HEADER
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Splits a query string into decoded `name=value` pairs.
+fn parse_query(query: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(name), decode(value))
+        })
+}
+
+/// Decodes `+` and `%XX` escapes, leaving malformed escapes as they are.
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => match text
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(byte) => {
+                    decoded.push(byte);
+                    i += 2;
+                }
+                None => decoded.push(b'%'),
+            },
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        422 => "Unprocessable Entity",
+        500 => "Internal Server Error",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Starts a server on a free port and returns its address.
+    fn start(timeout: Duration) -> SocketAddr {
+        let server = Server::bind("127.0.0.1:0", timeout).unwrap();
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+        addr
+    }
+
+    /// Sends one request and returns the status and the parsed JSON body.
+    fn request(addr: SocketAddr, method: &str, target: &str, body: &str) -> (u16, Value) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            target,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    #[test]
+    fn days_and_errors() {
+        let addr = start(Duration::from_secs(10));
+        let (status, days) = request(addr, "GET", "/days", "");
+        assert_eq!(200, status);
+        let listed: Vec<_> = days["days"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|day| day["day"].as_u64().unwrap() as usize)
+            .collect();
+        let solved: Vec<_> = (1..=SOLUTIONS.len())
+            .filter(|day| SOLUTIONS[day - 1].is_solved())
+            .collect();
+        assert_eq!(solved, listed);
+
+        assert_eq!(404, request(addr, "POST", "/day/25/part/1", "").0);
+        assert_eq!(404, request(addr, "GET", "/nowhere", "").0);
+        assert_eq!(405, request(addr, "DELETE", "/days", "").0);
+    }
+
+    #[test]
+    #[cfg(all(feature = "day01", feature = "day07"))]
+    fn solve() {
+        let addr = start(Duration::from_secs(10));
+        let (status, solved) = request(addr, "POST", "/day/1/part/2", "+3\n+3\n+4\n-2\n-4\n");
+        assert_eq!(200, status, "{}", solved);
+        assert_eq!(json!("10"), solved["answer"]);
+        assert_eq!(json!("default"), solved["variant"]);
+
+        let example = SOLUTIONS[6].examples[0].input;
+        let (status, solved) = request(
+            addr,
+            "POST",
+            "/day/7/part/2?workers=2&base%5Ftime=0",
+            example,
+        );
+        assert_eq!(200, status, "{}", solved);
+        assert_eq!(json!("15"), solved["answer"]);
+
+        let (status, error) = request(addr, "POST", "/day/7/part/2?workers=0", example);
+        assert_eq!(400, status);
+        assert_eq!(
+            json!("Parameter workers must be at least 1, got 0"),
+            error["error"]
+        );
+        assert_eq!(404, request(addr, "POST", "/day/1/part/3", "").0);
+        assert_eq!(422, request(addr, "POST", "/day/1/part/1", "one").0);
+    }
+
+    #[test]
+    fn limits() {
+        let long_header = format!("GET /days HTTP/1.1\r\nX: {}\r\n\r\n", "a".repeat(1 << 16));
+        let error = read_request(&mut long_header.as_bytes()).err().unwrap();
+        assert!(error.contains("over 65536 bytes"), "{}", error);
+        assert!(read_request(&mut "GET /days HTTP/1.1\r\n\r\n".as_bytes()).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "day01")]
+    fn timeout() {
+        let addr = start(Duration::from_millis(100));
+        let (status, error) = request(addr, "POST", "/day/1/part/2", "+1\n");
+        assert_eq!(422, status);
+        assert!(
+            error["error"].as_str().unwrap().starts_with("timed out"),
+            "{}",
+            error
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! Helpers shared by the per-day tests.
+
+// Which helpers are used depends on which days' features are enabled.
+#![allow(dead_code)]
+
+use std::{env, fmt::Write, fs, ops::Range, path::PathBuf, sync::Mutex};
+
+use crate::{Context, Explain, Fact, Progress, Variant};
+
+/// A small deterministic xorshift generator for building test inputs.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn range(&mut self, range: Range<u64>) -> u64 {
+        range.start + self.next_u64() % (range.end - range.start)
+    }
+}
+
+/// Runs every variant on `input` and asserts they all give the same answer.
+pub fn assert_variants_agree(variants: &[Variant], input: &str) {
+    let (reference, others) = variants.split_first().expect("no variants");
+    let expected = (reference.run)(input, &Context::default()).ok();
+    for variant in others {
+        assert_eq!(
+            expected,
+            (variant.run)(input, &Context::default()).ok(),
+            "variant {:?} disagrees with {:?} on input {:?}",
+            variant.name,
+            reference.name,
+            input,
+        );
+    }
+}
+
+/// Remembers every progress update it is sent.
+#[derive(Default)]
+pub struct RecordProgress(Mutex<Vec<(&'static str, u64, Option<u64>)>>);
+
+impl RecordProgress {
+    pub fn updates(&self) -> Vec<(&'static str, u64, Option<u64>)> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl Progress for RecordProgress {
+    fn update(&self, task: &'static str, position: u64, length: Option<u64>) {
+        self.0.lock().unwrap().push((task, position, length));
+    }
+}
+
+/// Remembers every fact it is told.
+#[derive(Default)]
+pub struct RecordFacts(Mutex<Vec<(&'static str, Fact)>>);
+
+impl RecordFacts {
+    pub fn facts(&self) -> Vec<(&'static str, Fact)> {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// The last value reported for `name`.
+    pub fn get(&self, name: &str) -> Option<Fact> {
+        let facts = self.0.lock().unwrap();
+        facts
+            .iter()
+            .rev()
+            .find(|(fact, _)| *fact == name)
+            .map(|(_, value)| value.clone())
+    }
+}
+
+impl Explain for RecordFacts {
+    fn fact(&self, name: &'static str, value: Fact) {
+        self.0.lock().unwrap().push((name, value));
+    }
+}
+
+/// Set to accept the current output of every snapshot test, writing it to
+/// the `.snap` files instead of comparing against them.
+pub const BLESS_VAR: &str = "AOC2018_BLESS";
+
+/// Asserts that `actual` matches the snapshot `src/snapshots/{name}.snap`,
+/// showing a line diff if it does not.
+///
+/// Run the tests with `AOC2018_BLESS=1` to create missing snapshots or
+/// accept changed ones, then review the `.snap` files like any other change.
+#[track_caller]
+pub fn assert_snapshot(name: &str, actual: &str) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "src", "snapshots"]
+        .iter()
+        .collect::<PathBuf>()
+        .join(format!("{}.snap", name));
+    if env::var_os(BLESS_VAR).is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = match fs::read_to_string(&path) {
+        Ok(expected) => expected,
+        Err(_) => panic!(
+            "Missing snapshot {}, run with {}=1 to create it:\n{}",
+            path.display(),
+            BLESS_VAR,
+            actual
+        ),
+    };
+    if expected != actual {
+        panic!(
+            "Snapshot {} does not match, run with {}=1 to accept the new output \
+             (- snapshot, + actual):\n{}",
+            path.display(),
+            BLESS_VAR,
+            diff(&expected, actual)
+        );
+    }
+}
+
+/// Shows the lines of `new` that are not in `old` with a `+` and those of
+/// `old` not in `new` with a `-`, from a longest common subsequence of lines.
+fn diff(old: &str, new: &str) -> String {
+    let old: Vec<_> = old.split('\n').collect();
+    let new: Vec<_> = new.split('\n').collect();
+    // common[i][j] is the length of the longest common subsequence of
+    // old[i..] and new[j..].
+    let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let mut diff = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            writeln!(diff, "  {}", old[i]).unwrap();
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            writeln!(diff, "- {}", old[i]).unwrap();
+            i += 1;
+        } else {
+            writeln!(diff, "+ {}", new[j]).unwrap();
+            j += 1;
+        }
+    }
+    diff
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_marks_changed_lines() {
+        assert_eq!("  a\n- b\n+ c\n  d\n+ e\n", diff("a\nb\nd", "a\nc\nd\ne"));
+    }
+}